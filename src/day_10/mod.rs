@@ -12,8 +12,13 @@ pub fn run() {
 
     println!("Day 10, Part 1: {}", part_1(&mut device));
 
-    println!("Day 10, Part 2:");
-    device.print_video_system_display();
+    match part_2(&device) {
+        Ok(letters) => println!("Day 10, Part 2: {}", letters),
+        Err(error) => {
+            println!("Day 10, Part 2: {}", error);
+            device.print_video_system_display();
+        }
+    }
 }
 
 fn part_1(device: &mut Device) -> i32 {
@@ -23,6 +28,10 @@ fn part_1(device: &mut Device) -> i32 {
         .fold(0, |acc, signal_strength| acc + signal_strength)
 }
 
+fn part_2(device: &Device) -> Result<String, String> {
+    device.read_video_system_display()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -52,5 +61,74 @@ mod tests {
 
         assert_eq!(part_1_result, part_1_expected);
         assert_eq!(part_2_result, part_2_expected);
+        assert!(part_2(&device).is_err());
+    }
+
+    /*
+    Every `addx` holds X for two cycles, so each pair of pixels gets a sprite position
+    that lights exactly the pixels of the pair that are lit in `rows`.
+    */
+    fn program_drawing(rows: &[&str]) -> Vec<String> {
+        let pixels: Vec<bool> = rows
+            .iter()
+            .flat_map(|row| row.chars().map(|pixel| pixel == '#'))
+            .collect();
+
+        let sprite_positions: Vec<i32> = pixels
+            .chunks(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                let col = (index * 2 % 40) as i32;
+
+                match (pair[0], pair[1]) {
+                    (true, true) => col + 1,
+                    (true, false) => col - 1,
+                    (false, true) => col + 2,
+                    (false, false) => col + 4,
+                }
+            })
+            .collect();
+
+        let mut x = 1;
+
+        assert_eq!(sprite_positions[0], x);
+
+        let mut result: Vec<String> = sprite_positions[1..]
+            .iter()
+            .map(|&sprite_position| {
+                let instruction = format!("addx {}", sprite_position - x);
+
+                x = sprite_position;
+
+                instruction
+            })
+            .collect();
+
+        result.extend([String::from("noop"), String::from("noop")]);
+
+        result
+    }
+
+    #[test]
+    fn test_read_display_from_program() {
+        let program = program_drawing(&[
+            "####.#.....##..###...##..###..#..#..###.",
+            "#....#....#..#.#..#.#..#.#..#.#..#.#....",
+            "###..#....#..#.#..#.#..#.###..#..#.#....",
+            "#....#....#..#.###..####.#..#.#..#..##..",
+            "#....#....#..#.#.#..#..#.#..#.#..#....#.",
+            "#....####..##..#..#.#..#.###...##..###..",
+        ]);
+
+        let mut device = Device::new();
+
+        device.add_video_system_cpu_instructions(&program);
+        device.run_video_system_until_tick(240);
+
+        let expected = Ok(String::from("FLORABUS"));
+
+        let result = part_2(&device);
+
+        assert_eq!(result, expected);
     }
 }
@@ -0,0 +1,338 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::worry::Worry;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Operator> {
+        match c {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Subtract),
+            '*' => Some(Operator::Multiply),
+            '/' => Some(Operator::Divide),
+            '%' => Some(Operator::Remainder),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+            Operator::Remainder => '%',
+        }
+    }
+
+    pub fn divides(&self) -> bool {
+        matches!(self, Operator::Divide | Operator::Remainder)
+    }
+
+    fn binds_tightly(&self) -> bool {
+        matches!(
+            self,
+            Operator::Multiply | Operator::Divide | Operator::Remainder
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Old,
+    Constant(i64),
+    Binary(Box<Expression>, Operator, Box<Expression>),
+}
+
+impl Expression {
+    pub fn evaluate<W: Worry>(&self, old: &W) -> Result<W, String> {
+        match self {
+            Expression::Old => Ok(old.clone()),
+            Expression::Constant(value) => Ok(old.lift(*value)),
+            Expression::Binary(left, operator, right) => left
+                .evaluate(old)?
+                .checked_apply(*operator, &right.evaluate(old)?),
+        }
+    }
+}
+
+struct ExpressionParser {
+    input: Vec<char>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn new(input: &str) -> ExpressionParser {
+        ExpressionParser {
+            input: input.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|c| c.is_whitespace())
+        {
+            self.position += 1;
+        }
+
+        self.input.get(self.position).cloned()
+    }
+
+    fn unexpected(&mut self, expected: &str) -> String {
+        match self.peek() {
+            Some(c) => format!(
+                "Expected {} at position {}, found '{}'",
+                expected, self.position, c
+            ),
+            None => format!(
+                "Expected {} at position {}, found the end of the expression",
+                expected, self.position
+            ),
+        }
+    }
+
+    // Sums of products, so `old + 2 * old` multiplies first.
+    fn parse_expression(&mut self) -> Result<Expression, String> {
+        let mut result = self.parse_term()?;
+
+        while let Some(operator) = self
+            .peek()
+            .and_then(Operator::from_char)
+            .filter(|operator| !operator.binds_tightly())
+        {
+            self.position += 1;
+
+            result = Expression::Binary(Box::new(result), operator, Box::new(self.parse_term()?));
+        }
+
+        Ok(result)
+    }
+
+    fn parse_term(&mut self) -> Result<Expression, String> {
+        let mut result = self.parse_factor()?;
+
+        while let Some(operator) = self
+            .peek()
+            .and_then(Operator::from_char)
+            .filter(|operator| operator.binds_tightly())
+        {
+            self.position += 1;
+            self.peek();
+
+            let start = self.position;
+            let right = self.parse_factor()?;
+
+            if operator.divides() && (right == Expression::Constant(0)) {
+                return Err(format!("Division by zero at position {}", start));
+            }
+
+            result = Expression::Binary(Box::new(result), operator, Box::new(right));
+        }
+
+        Ok(result)
+    }
+
+    fn parse_factor(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+
+                let result = self.parse_expression()?;
+
+                if self.peek() != Some(')') {
+                    return Err(self.unexpected("')'"));
+                }
+
+                self.position += 1;
+
+                Ok(result)
+            }
+            Some(c) if c.is_ascii_digit() => self.parse_constant(),
+            Some(c) if c.is_alphabetic() => self.parse_variable(),
+            _ => Err(self.unexpected("a number, `old` or '('")),
+        }
+    }
+
+    fn parse_constant(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|c| c.is_ascii_digit())
+        {
+            self.position += 1;
+        }
+
+        let digits: String = self.input[start..self.position].iter().collect();
+
+        digits
+            .parse()
+            .map(Expression::Constant)
+            .map_err(|_| format!("Number at position {} is too large", start))
+    }
+
+    fn parse_variable(&mut self) -> Result<Expression, String> {
+        let start = self.position;
+
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(|c| c.is_alphanumeric())
+        {
+            self.position += 1;
+        }
+
+        let name: String = self.input[start..self.position].iter().collect();
+
+        match name.as_str() {
+            "old" => Ok(Expression::Old),
+            _ => Err(format!("Unknown variable `{}` at position {}", name, start)),
+        }
+    }
+}
+
+impl FromStr for Expression {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Expression, String> {
+        let mut parser = ExpressionParser::new(input);
+
+        let result = parser.parse_expression()?;
+
+        if parser.peek().is_some() {
+            return Err(parser.unexpected("an operator"));
+        }
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expression::Old => write!(f, "old"),
+            Expression::Constant(value) => write!(f, "{}", value),
+            Expression::Binary(left, operator, right) => {
+                for (index, operand) in [left, right].iter().enumerate() {
+                    if index == 1 {
+                        write!(f, " {} ", operator.symbol())?;
+                    }
+
+                    match operand.as_ref() {
+                        Expression::Binary(..) => write!(f, "({})", operand)?,
+                        _ => write!(f, "{}", operand)?,
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::big_uint::BigUint;
+
+    #[test]
+    fn test_expression_from_str() {
+        let expected = Expression::Binary(
+            Box::new(Expression::Old),
+            Operator::Multiply,
+            Box::new(Expression::Constant(19)),
+        );
+
+        let result = "old * 19".parse();
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_expression_precedence_and_parentheses() {
+        let input = [
+            "old + 2 * old",
+            "(old + 2) * old",
+            "old - 10 - 3",
+            "old * old % 7",
+        ];
+
+        let expected = vec![15, 35, -8, 4];
+
+        let result: Vec<i64> = input
+            .iter()
+            .map(|line| line.parse::<Expression>().unwrap().evaluate(&5).unwrap())
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_expression_display_round_trip() {
+        let input = ["old * old", "(old + 2) * 3", "old - (10 - 3)", "old"];
+
+        let expected: Vec<Expression> = input.iter().map(|line| line.parse().unwrap()).collect();
+
+        let result: Vec<Expression> = expected
+            .iter()
+            .map(|expression| expression.to_string().parse().unwrap())
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        let input = ["old ^ 2", "new + 1", "(old + 1", "old /", "old % 0", ""];
+
+        let expected =
+            vec![
+            Err(String::from("Expected an operator at position 4, found '^'")),
+            Err(String::from("Unknown variable `new` at position 0")),
+            Err(String::from(
+                "Expected ')' at position 8, found the end of the expression",
+            )),
+            Err(String::from(
+                "Expected a number, `old` or '(' at position 5, found the end of the expression",
+            )),
+            Err(String::from("Division by zero at position 6")),
+            Err(String::from(
+                "Expected a number, `old` or '(' at position 0, found the end of the expression",
+            )),
+        ];
+
+        let result: Vec<Result<Expression, String>> =
+            input.iter().map(|line| line.parse()).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_evaluate_division_by_zero() {
+        let expression: Expression = "old / (old - old)".parse().unwrap();
+
+        let expected = Err(String::from("5 / 0 is a division by zero"));
+
+        let result = expression.evaluate(&5_i64);
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            expression.evaluate(&BigUint::from(5)),
+            Err(String::from("5 / 0 is a division by zero"))
+        );
+    }
+}
@@ -1,89 +1,61 @@
+mod expression;
 mod monkey;
-
-use std::collections::HashMap;
+mod residue;
+mod trajectory;
+mod troop;
+mod worry;
 
 use crate::util::file_reader::to_string_vector;
-use crate::util::math::lcm;
 
-use monkey::Monkey;
+use troop::{DivideBy, MonkeyTroop, NoRelief};
 
 pub fn run() {
     let input = to_string_vector("inputs/day_11.txt").expect("Something went wrong Day 11!");
 
-    println!("Day 11, Part 1: {}", part_1(&input));
-    println!("Day 11, Part 2: {}", part_2(&input));
-}
-
-fn part_1(input: &[String]) -> usize {
-    let mut monkeys: HashMap<usize, Monkey> = input
-        .split(|line| line.is_empty())
-        .map(|props| Monkey::from(props))
-        .map(|monkey| (monkey.id, monkey))
-        .collect();
-
-    let number_of_monkeys = monkeys.len();
-
-    let total_passes = 20 * number_of_monkeys;
-
-    for pass in 0..total_passes {
-        let monkey_id = pass % number_of_monkeys;
-
-        let current_monkey = monkeys.get_mut(&monkey_id).unwrap();
-
-        for (next_monkey_id, worry_value) in current_monkey.take_turn(|worry_level| worry_level / 3)
-        {
-            let next_monkey = monkeys.get_mut(&next_monkey_id).unwrap();
+    let troop = match MonkeyTroop::parse(&input) {
+        Ok(troop) => troop,
+        Err(error) => {
+            println!("Day 11: {}", error);
 
-            next_monkey.take_item(worry_value);
+            return;
         }
-    }
-
-    let mut monkey_number_items_inspected: Vec<usize> = monkeys
-        .into_iter()
-        .map(|(_, monkey)| monkey.number_of_items_inspected)
-        .collect();
+    };
 
-    monkey_number_items_inspected.sort_by(|a, b| b.cmp(a));
+    match part_1(&troop) {
+        Ok(monkey_business) => println!("Day 11, Part 1: {}", monkey_business),
+        Err(error) => println!("Day 11, Part 1: {}", error),
+    }
 
-    monkey_number_items_inspected[0] * monkey_number_items_inspected[1]
+    match part_2(&troop) {
+        Ok(monkey_business) => println!("Day 11, Part 2: {}", monkey_business),
+        Err(error) => println!("Day 11, Part 2: {}", error),
+    }
 }
 
-fn part_2(input: &[String]) -> usize {
-    let mut monkeys: HashMap<usize, Monkey> = input
-        .split(|line| line.is_empty())
-        .map(|props| Monkey::from(props))
-        .map(|monkey| (monkey.id, monkey))
-        .collect();
+fn part_1(troop: &MonkeyTroop) -> Result<usize, String> {
+    let mut checked_troop = troop.clone();
 
-    let number_of_monkeys = monkeys.len();
-    let total_lcm = monkeys
-        .values()
-        .fold(1, |acc, monkey| lcm(acc, monkey.must_divide_by()));
-
-    let total_passes = 10_000 * number_of_monkeys;
-
-    for pass in 0..total_passes {
-        let monkey_id = pass % number_of_monkeys;
-
-        let current_monkey = monkeys.get_mut(&monkey_id).unwrap();
+    if checked_troop.run(20, &DivideBy(3)).is_ok() {
+        return Ok(checked_troop.monkey_business(2));
+    }
 
-        let next_monkeys = current_monkey.take_turn(|worry_level| worry_level % total_lcm);
+    // The worry levels overflowed, so start over with ones that can't.
+    let mut big_troop = troop.clone().into_big_uint()?;
 
-        for (next_monkey_id, worry_value) in next_monkeys {
-            let next_monkey = monkeys.get_mut(&next_monkey_id).unwrap();
+    big_troop.run(20, &DivideBy(3))?;
 
-            next_monkey.take_item(worry_value);
-        }
-    }
+    Ok(big_troop.monkey_business(2))
+}
 
-    let mut monkey_number_items_inspected: Vec<usize> = monkeys
-        .into_iter()
-        .map(|(_, monkey)| monkey.number_of_items_inspected)
-        .collect();
+fn part_2(troop: &MonkeyTroop) -> Result<u128, String> {
+    troop.extrapolate_monkey_business(10_000, 2).or_else(|_| {
+        // Even modular worry levels overflowed, so simulate every round on residues instead.
+        let mut residue_troop = troop.clone().into_residues();
 
-    monkey_number_items_inspected.sort_by(|a, b| b.cmp(a));
+        residue_troop.run(10_000, &NoRelief)?;
 
-    monkey_number_items_inspected[0] * monkey_number_items_inspected[1]
+        Ok(residue_troop.monkey_business(2) as u128)
+    })
 }
 
 #[cfg(test)]
@@ -122,9 +94,11 @@ mod tests {
             String::from("    If false: throw to monkey 1"),
         ];
 
-        let expected = 10_605;
+        let troop = MonkeyTroop::parse(&input).unwrap();
+
+        let expected = Ok(10_605);
 
-        let result = part_1(&input);
+        let result = part_1(&troop);
 
         assert_eq!(result, expected);
     }
@@ -161,10 +135,35 @@ mod tests {
             String::from("    If false: throw to monkey 1"),
         ];
 
-        let expected = 2_713_310_158;
+        let troop = MonkeyTroop::parse(&input).unwrap();
+
+        let expected = Ok(2_713_310_158);
+
+        let result = part_2(&troop);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_part_1_falls_back_to_big_integers() {
+        // Monkey 2 squares its items, which takes this one past what an i64 can hold.
+        let input: Vec<String> = to_string_vector("test_inputs/day_11_part_1.txt")
+            .unwrap()
+            .iter()
+            .map(|line| line.replace("79, 60, 97", "4000000000"))
+            .collect();
+
+        let troop = MonkeyTroop::parse(&input).unwrap();
+
+        let mut big_troop = troop.clone().into_big_uint().unwrap();
+
+        big_troop.run(20, &DivideBy(3)).unwrap();
+
+        let expected = Ok(big_troop.monkey_business(2));
 
-        let result = part_2(&input);
+        let result = part_1(&troop);
 
+        assert!(troop.clone().run(20, &DivideBy(3)).is_err());
         assert_eq!(result, expected);
     }
 }
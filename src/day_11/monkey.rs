@@ -1,62 +1,91 @@
 use std::collections::VecDeque;
-use std::num::ParseIntError;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
-pub struct Monkey {
+use super::expression::Expression;
+use super::worry::Worry;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Monkey<W = i64> {
     pub id: usize,
-    items: VecDeque<i64>,
+    items: VecDeque<W>,
     pub number_of_items_inspected: usize,
-    operation: char,
-    operation_scalar: Option<i64>,
+    operation: Expression,
     test_scalar: i64,
     test_true_monkey_id: usize,
     test_false_monkey_id: usize,
 }
 
-impl Monkey {
-    pub fn take_turn<F>(&mut self, worry_function: F) -> Vec<(usize, i64)>
+impl<W: Worry> Monkey<W> {
+    pub fn take_turn<F>(&mut self, worry_function: F) -> Result<Vec<(usize, W)>, String>
     where
-        F: FnOnce(i64) -> i64 + Copy,
+        F: Fn(W) -> Result<W, String>,
     {
         let mut result = vec![];
 
-        while let Some(mut worry_level) = self.items.pop_front() {
+        while let Some(worry_level) = self.items.pop_front() {
             self.number_of_items_inspected += 1;
 
-            worry_level = self.apply_operation(worry_level);
-
-            worry_level = worry_function(worry_level);
+            result.push(self.inspect(&worry_level, &worry_function)?);
+        }
 
-            let to_monkey = self.get_monkey_to_throw_to(worry_level);
+        Ok(result)
+    }
 
-            result.push((to_monkey, worry_level));
-        }
+    // Where a single item is thrown and with what worry level, without touching the monkey.
+    pub fn inspect<F>(&self, worry_level: &W, worry_function: F) -> Result<(usize, W), String>
+    where
+        F: Fn(W) -> Result<W, String>,
+    {
+        let worry_level = worry_function(self.apply_operation(worry_level)?)
+            .map_err(|error| format!("Monkey {}: {}", self.id, error))?;
 
-        result
+        Ok((self.get_monkey_to_throw_to(&worry_level), worry_level))
     }
 
-    pub fn take_item(&mut self, worry_value: i64) {
+    pub fn take_item(&mut self, worry_value: W) {
         self.items.push_back(worry_value);
     }
 
+    pub fn items(&self) -> Vec<W> {
+        self.items.iter().cloned().collect()
+    }
+
     pub fn must_divide_by(&self) -> i64 {
         self.test_scalar
     }
 
-    fn apply_operation(&self, worry_level: i64) -> i64 {
-        let scalar = self.operation_scalar.unwrap_or(worry_level);
+    #[cfg(test)]
+    pub fn operation(&self) -> &Expression {
+        &self.operation
+    }
 
-        match &self.operation {
-            '+' => worry_level + scalar,
-            '-' => worry_level - scalar,
-            '*' => worry_level * scalar,
-            '/' => worry_level / scalar,
-            _ => panic!("Unknown operation: {}", self.operation),
-        }
+    // The monkeys thrown to when the test passes and when it fails.
+    pub fn throw_targets(&self) -> (usize, usize) {
+        (self.test_true_monkey_id, self.test_false_monkey_id)
     }
 
-    fn get_monkey_to_throw_to(&self, worry_level: i64) -> usize {
+    pub fn try_map_worry<V, F>(self, convert: F) -> Result<Monkey<V>, String>
+    where
+        F: Fn(&W) -> Result<V, String>,
+    {
+        Ok(Monkey {
+            id: self.id,
+            items: self.items.iter().map(convert).collect::<Result<_, _>>()?,
+            number_of_items_inspected: self.number_of_items_inspected,
+            operation: self.operation,
+            test_scalar: self.test_scalar,
+            test_true_monkey_id: self.test_true_monkey_id,
+            test_false_monkey_id: self.test_false_monkey_id,
+        })
+    }
+
+    fn apply_operation(&self, worry_level: &W) -> Result<W, String> {
+        self.operation
+            .evaluate(worry_level)
+            .map_err(|error| format!("Monkey {}: {}", self.id, error))
+    }
+
+    fn get_monkey_to_throw_to(&self, worry_level: &W) -> usize {
         if self.run_test(worry_level) {
             self.test_true_monkey_id
         } else {
@@ -64,55 +93,89 @@ impl Monkey {
         }
     }
 
-    fn run_test(&self, worry_level: i64) -> bool {
-        worry_level % self.test_scalar == 0
+    fn run_test(&self, worry_level: &W) -> bool {
+        worry_level.is_divisible_by(self.test_scalar)
     }
 }
 
-impl From<&[String]> for Monkey {
-    fn from(input: &[String]) -> Monkey {
-        let id = get_only_digit(&input[0]).unwrap();
-
-        let items = input[1][18..]
-            .split(", ")
-            .filter_map(|item| item.parse().ok())
-            .collect();
+impl Monkey {
+    pub fn parse(input: &[String]) -> Result<Monkey, String> {
+        let id = parse_field(input.first(), "Monkey <id>:")?;
+
+        let with_id = |error: String| format!("Monkey {}: {}", id, error);
+
+        let items = parse_field::<String>(input.get(1), "Starting items: <items>")
+            .map_err(with_id)?
+            .split(',')
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                item.parse()
+                    .map_err(|_| format!("Monkey {}: invalid item `{}`", id, item))
+            })
+            .collect::<Result<_, _>>()?;
 
         let number_of_items_inspected = 0;
 
-        let operation_info = input[2][23..].split_once(' ').unwrap();
+        let operation = parse_field::<String>(input.get(2), "Operation: new = <expression>")
+            .map_err(with_id)?
+            .parse()
+            .map_err(|error| format!("Monkey {}: invalid operation: {}", id, error))?;
 
-        let operation = operation_info.0.chars().nth(0).unwrap();
+        let test_scalar =
+            parse_field(input.get(3), "Test: divisible by <number>").map_err(with_id)?;
 
-        let operation_scalar = operation_info.1.parse().ok();
+        // Residues and unsigned worry levels need a positive modulus.
+        if test_scalar <= 0 {
+            return Err(format!(
+                "Monkey {}: can't test for divisibility by {}",
+                id, test_scalar
+            ));
+        }
 
-        let test_scalar = get_only_digit(&input[3]).unwrap();
-        let test_true_monkey_id = get_only_digit(&input[4]).unwrap();
-        let test_false_monkey_id = get_only_digit(&input[5]).unwrap();
+        let test_true_monkey_id =
+            parse_field(input.get(4), "If true: throw to monkey <id>").map_err(with_id)?;
+        let test_false_monkey_id =
+            parse_field(input.get(5), "If false: throw to monkey <id>").map_err(with_id)?;
 
-        Monkey {
+        if let Some(line) = input.get(6) {
+            return Err(format!("Monkey {}: unexpected line: {}", id, line.trim()));
+        }
+
+        Ok(Monkey {
             id,
             items,
             number_of_items_inspected,
             operation,
-            operation_scalar,
             test_scalar,
             test_true_monkey_id,
             test_false_monkey_id,
-        }
+        })
+    }
+}
+
+impl From<&[String]> for Monkey {
+    fn from(input: &[String]) -> Monkey {
+        Monkey::parse(input).unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
-fn get_only_digit<T>(input: &str) -> Option<T>
-where
-    T: FromStr<Err = ParseIntError>,
-{
-    input
-        .chars()
-        .filter(|c| c.is_digit(10))
-        .collect::<String>()
-        .parse()
-        .ok()
+/*
+Parses the `<...>` placeholder out of a line shaped like `pattern`, ignoring the
+indentation and any whitespace around the value.
+*/
+fn parse_field<T: FromStr>(line: Option<&String>, pattern: &str) -> Result<T, String> {
+    let (prefix, rest) = pattern.split_once('<').unwrap();
+    let (_, suffix) = rest.split_once('>').unwrap();
+
+    let line = line
+        .map(|line| line.trim())
+        .ok_or_else(|| format!("expected `{}`, found the end of the monkey", pattern))?;
+
+    line.strip_prefix(prefix.trim())
+        .and_then(|value| value.strip_suffix(suffix.trim()))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| format!("expected `{}`, found: {}", pattern, line))
 }
 
 #[cfg(test)]
@@ -134,8 +197,7 @@ mod tests {
             id: 0,
             items: VecDeque::from([79, 98]),
             number_of_items_inspected: 0,
-            operation: '*',
-            operation_scalar: Some(19),
+            operation: "old * 19".parse().unwrap(),
             test_scalar: 23,
             test_true_monkey_id: 2,
             test_false_monkey_id: 3,
@@ -161,8 +223,7 @@ mod tests {
             id: 0,
             items: VecDeque::from([79, 98]),
             number_of_items_inspected: 0,
-            operation: '+',
-            operation_scalar: None,
+            operation: "old + old".parse().unwrap(),
             test_scalar: 23,
             test_true_monkey_id: 2,
             test_false_monkey_id: 3,
@@ -188,7 +249,7 @@ mod tests {
 
         let expected = vec![(2, 46)];
 
-        let result = monkey.take_turn(|worry_level| worry_level / 3);
+        let result = monkey.take_turn(|worry_level| Ok(worry_level / 3)).unwrap();
 
         assert_eq!(result, expected);
     }
@@ -208,7 +269,140 @@ mod tests {
 
         let expected = vec![(3, 500), (3, 620)];
 
-        let result = monkey.take_turn(|worry_level| worry_level / 3);
+        let result = monkey.take_turn(|worry_level| Ok(worry_level / 3)).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_monkey_parse_expression_operation() {
+        let input = vec![
+            String::from("Monkey 0:"),
+            String::from("  Starting items: 10"),
+            String::from("  Operation: new = (old + 2) * old - 1"),
+            String::from("  Test: divisible by 5"),
+            String::from("    If true: throw to monkey 2"),
+            String::from("    If false: throw to monkey 3"),
+        ];
+
+        let mut monkey = Monkey::parse(input.as_slice()).unwrap();
+
+        let expected = vec![(3, 119)];
+
+        let result = monkey.take_turn(Ok).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_monkey_parse_invalid_operation() {
+        let input = vec![
+            String::from("Monkey 4:"),
+            String::from("  Starting items: 79, 98"),
+            String::from("  Operation: new = old ** 2"),
+            String::from("  Test: divisible by 23"),
+            String::from("    If true: throw to monkey 2"),
+            String::from("    If false: throw to monkey 3"),
+        ];
+
+        let expected = Err(String::from(
+            "Monkey 4: invalid operation: Expected a number, `old` or '(' at position 5, found '*'",
+        ));
+
+        let result = Monkey::parse(input.as_slice());
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_monkey_parse_multi_digit_ids() {
+        let input = vec![
+            String::from("Monkey 12:"),
+            String::from("  Starting items:"),
+            String::from("  Operation: new = old * 2"),
+            String::from("  Test: divisible by 101"),
+            String::from("    If true: throw to monkey 10"),
+            String::from("    If false: throw to monkey 3"),
+        ];
+
+        let expected = Monkey {
+            id: 12,
+            items: VecDeque::new(),
+            number_of_items_inspected: 0,
+            operation: "old * 2".parse().unwrap(),
+            test_scalar: 101,
+            test_true_monkey_id: 10,
+            test_false_monkey_id: 3,
+        };
+
+        let result = Monkey::parse(input.as_slice());
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_monkey_parse_errors() {
+        let valid = [
+            "Monkey 1:",
+            "  Starting items: 79, 98",
+            "  Operation: new = old + 1",
+            "  Test: divisible by 23",
+            "    If true: throw to monkey 2",
+            "    If false: throw to monkey 3",
+        ];
+
+        let input = [
+            (0, "Monkey one:"),
+            (1, "  Starting items: 79, 9x"),
+            (3, "  Test: divisible by 2 or 3"),
+            (3, "  Test: divisible by 0"),
+            (3, "  Test: divisible by -7"),
+            (4, "    If true: throw to monkey 2 and 4"),
+            (5, "    If false: drop it"),
+        ];
+
+        let expected = vec![
+            Err(String::from("expected `Monkey <id>:`, found: Monkey one:")),
+            Err(String::from("Monkey 1: invalid item `9x`")),
+            Err(String::from(
+                "Monkey 1: expected `Test: divisible by <number>`, found: Test: divisible by 2 or 3",
+            )),
+            Err(String::from("Monkey 1: can't test for divisibility by 0")),
+            Err(String::from("Monkey 1: can't test for divisibility by -7")),
+            Err(String::from(
+                "Monkey 1: expected `If true: throw to monkey <id>`, found: If true: throw to monkey 2 and 4",
+            )),
+            Err(String::from(
+                "Monkey 1: expected `If false: throw to monkey <id>`, found: If false: drop it",
+            )),
+        ];
+
+        let result: Vec<Result<Monkey, String>> = input
+            .iter()
+            .map(|(index, line)| {
+                let mut lines: Vec<String> = valid.iter().map(|line| line.to_string()).collect();
+
+                lines[*index] = line.to_string();
+
+                Monkey::parse(lines.as_slice())
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_monkey_parse_line_count() {
+        let input: Vec<String> = ["Monkey 1:", "  Starting items: 79, 98"]
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+
+        let expected = Err(String::from(
+            "Monkey 1: expected `Operation: new = <expression>`, found the end of the monkey",
+        ));
+
+        let result = Monkey::parse(input.as_slice());
 
         assert_eq!(result, expected);
     }
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use super::expression::Operator;
+use super::worry::Worry;
+
+/*
+A worry level kept as its remainder modulo each monkey's divisor. That is all the
+tests ever look at, and addition and multiplication work on each remainder on its
+own, so the numbers stay small however many rounds go by. Division can't be done
+this way, so operations that divide are reported as errors.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Residues {
+    moduli: Vec<i64>,
+    residues: Vec<i64>,
+}
+
+impl Residues {
+    pub fn new(value: i64, moduli: &[i64]) -> Residues {
+        Residues {
+            moduli: moduli.to_vec(),
+            residues: moduli
+                .iter()
+                .map(|modulus| value.rem_euclid(*modulus))
+                .collect(),
+        }
+    }
+}
+
+impl Worry for Residues {
+    fn lift(&self, value: i64) -> Residues {
+        Residues::new(value, &self.moduli)
+    }
+
+    fn checked_apply(&self, operator: Operator, other: &Residues) -> Result<Residues, String> {
+        let combine: fn(i128, i128) -> i128 = match operator {
+            Operator::Add => |left, right| left + right,
+            Operator::Subtract => |left, right| left - right,
+            Operator::Multiply => |left, right| left * right,
+            Operator::Divide | Operator::Remainder => {
+                return Err(format!(
+                    "{} {} {} can't be computed on residues",
+                    self,
+                    operator.symbol(),
+                    other
+                ))
+            }
+        };
+
+        let residues = self
+            .residues
+            .iter()
+            .zip(other.residues.iter())
+            .zip(self.moduli.iter())
+            .map(|((&left, &right), &modulus)| {
+                combine(left as i128, right as i128).rem_euclid(modulus as i128) as i64
+            })
+            .collect();
+
+        Ok(Residues {
+            moduli: self.moduli.clone(),
+            residues,
+        })
+    }
+
+    fn is_divisible_by(&self, divisor: i64) -> bool {
+        self.moduli
+            .iter()
+            .zip(self.residues.iter())
+            .find(|(modulus, _)| *modulus % divisor == 0)
+            .map(|(_, residue)| residue % divisor == 0)
+            .expect("Residues are kept for every monkey's divisor")
+    }
+}
+
+impl fmt::Display for Residues {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self
+            .residues
+            .iter()
+            .zip(self.moduli.iter())
+            .map(|(residue, modulus)| format!("{} mod {}", residue, modulus))
+            .collect();
+
+        write!(f, "({})", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_residues_match_plain_arithmetic() {
+        let moduli = [23, 19, 13, 17];
+        let old = Residues::new(79, &moduli);
+
+        let expected = Residues::new(79 * 79 + 6, &moduli);
+
+        let result = old
+            .checked_apply(Operator::Multiply, &old)
+            .and_then(|square| square.checked_apply(Operator::Add, &old.lift(6)));
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_residues_is_divisible_by() {
+        let moduli = [23, 19, 13, 17];
+
+        let result: Vec<bool> = moduli
+            .iter()
+            .map(|&divisor| Residues::new(19 * 17 * 4, &moduli).is_divisible_by(divisor))
+            .collect();
+
+        assert_eq!(result, vec![false, true, false, true]);
+    }
+
+    #[test]
+    fn test_residues_cannot_divide() {
+        let old = Residues::new(10, &[3]);
+
+        let expected = Err(String::from(
+            "(1 mod 3) / (2 mod 3) can't be computed on residues",
+        ));
+
+        let result = old.checked_apply(Operator::Divide, &old.lift(2));
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ItemState {
+    pub monkey: usize,
+    pub worry_level: i64,
+}
+
+/*
+The monkeys that inspect a single item in each round. Under modular worry an item only
+has finitely many states and never depends on the other items, so after `lead_in`
+rounds it repeats the same `cycle_length` rounds forever.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ItemTrajectory {
+    inspections: Vec<Vec<usize>>,
+    lead_in: usize,
+}
+
+impl ItemTrajectory {
+    // `advance` plays one round for the item, returning its next state and who inspected it.
+    pub fn trace<F>(start: ItemState, mut advance: F) -> Result<ItemTrajectory, String>
+    where
+        F: FnMut(ItemState) -> Result<(ItemState, Vec<usize>), String>,
+    {
+        let mut seen = HashMap::new();
+        let mut inspections = vec![];
+        let mut state = start;
+
+        while !seen.contains_key(&state) {
+            seen.insert(state, inspections.len());
+
+            let (next_state, inspected_by) = advance(state)?;
+
+            inspections.push(inspected_by);
+            state = next_state;
+        }
+
+        Ok(ItemTrajectory {
+            inspections,
+            lead_in: seen[&state],
+        })
+    }
+
+    #[cfg(test)]
+    pub fn lead_in(&self) -> usize {
+        self.lead_in
+    }
+
+    pub fn cycle_length(&self) -> usize {
+        self.inspections.len() - self.lead_in
+    }
+
+    pub fn inspections_by_monkey(&self, rounds: u64, number_of_monkeys: usize) -> Vec<u64> {
+        let mut result = vec![0; number_of_monkeys];
+
+        let mut add_rounds = |from: usize, to: usize, times: u64| {
+            for inspected_by in &self.inspections[from..to] {
+                for &monkey in inspected_by {
+                    result[monkey] += times;
+                }
+            }
+        };
+
+        let lead_in = self.lead_in as u64;
+
+        if rounds <= lead_in {
+            add_rounds(0, rounds as usize, 1);
+        } else {
+            let cycle_length = self.cycle_length() as u64;
+            let full_cycles = (rounds - lead_in) / cycle_length;
+            let remaining_rounds = ((rounds - lead_in) % cycle_length) as usize;
+
+            add_rounds(0, self.lead_in, 1);
+            add_rounds(self.lead_in, self.inspections.len(), full_cycles);
+            add_rounds(self.lead_in, self.lead_in + remaining_rounds, 1);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Monkey 0 inspects once and hands over to monkey 1, which counts the worry level to 4 and back to 2.
+    fn get_trajectory() -> ItemTrajectory {
+        ItemTrajectory::trace(
+            ItemState {
+                monkey: 0,
+                worry_level: 0,
+            },
+            |state| {
+                let worry_level = if state.worry_level == 4 {
+                    2
+                } else {
+                    state.worry_level + 1
+                };
+
+                Ok((
+                    ItemState {
+                        monkey: 1,
+                        worry_level,
+                    },
+                    vec![state.monkey],
+                ))
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trace_finds_cycle() {
+        let trajectory = get_trajectory();
+
+        let expected = (2, 3);
+
+        let result = (trajectory.lead_in(), trajectory.cycle_length());
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_inspections_by_monkey() {
+        let trajectory = get_trajectory();
+
+        let expected = vec![vec![0, 0], vec![1, 0], vec![1, 4], vec![1, 999_999_999_999]];
+
+        let result: Vec<Vec<u64>> = [0, 1, 5, 1_000_000_000_000]
+            .iter()
+            .map(|&rounds| trajectory.inspections_by_monkey(rounds, 2))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_trace_propagates_errors() {
+        let expected = Err(String::from("Monkey 0: 1 / 0 overflows"));
+
+        let result = ItemTrajectory::trace(
+            ItemState {
+                monkey: 0,
+                worry_level: 1,
+            },
+            |_| Err(String::from("Monkey 0: 1 / 0 overflows")),
+        );
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,680 @@
+use crate::util::big_uint::BigUint;
+use crate::util::math::lcm;
+
+use super::monkey::Monkey;
+use super::residue::Residues;
+use super::trajectory::{ItemState, ItemTrajectory};
+use super::worry::Worry;
+
+pub trait WorryPolicy<W = i64> {
+    fn relieve(&self, worry_level: W) -> Result<W, String>;
+}
+
+// Part 1: relief that the item wasn't damaged divides the worry level.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DivideBy(pub i64);
+
+impl WorryPolicy<i64> for DivideBy {
+    fn relieve(&self, worry_level: i64) -> Result<i64, String> {
+        worry_level
+            .checked_div(self.0)
+            .ok_or_else(|| format!("Can't divide worry level {} by {}", worry_level, self.0))
+    }
+}
+
+impl WorryPolicy<BigUint> for DivideBy {
+    fn relieve(&self, worry_level: BigUint) -> Result<BigUint, String> {
+        u64::try_from(self.0)
+            .ok()
+            .and_then(|divisor| worry_level.div_rem_u64(divisor))
+            .map(|(quotient, _)| quotient)
+            .ok_or_else(|| format!("Can't divide worry level {} by {}", worry_level, self.0))
+    }
+}
+
+/*
+Part 2: no relief, so keep worry levels small by working modulo a multiple of every
+monkey's divisor, which leaves the outcome of every test unchanged.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ModuloBy(pub i64);
+
+impl WorryPolicy<i64> for ModuloBy {
+    fn relieve(&self, worry_level: i64) -> Result<i64, String> {
+        worry_level
+            .checked_rem(self.0)
+            .ok_or_else(|| format!("Can't take worry level {} modulo {}", worry_level, self.0))
+    }
+}
+
+impl WorryPolicy<BigUint> for ModuloBy {
+    fn relieve(&self, worry_level: BigUint) -> Result<BigUint, String> {
+        u64::try_from(self.0)
+            .ok()
+            .and_then(|modulus| worry_level.div_rem_u64(modulus))
+            .map(|(_, remainder)| BigUint::from(remainder))
+            .ok_or_else(|| format!("Can't take worry level {} modulo {}", worry_level, self.0))
+    }
+}
+
+// Worry levels grow unchecked, which only stays representable with big or residue numbers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NoRelief;
+
+impl<W> WorryPolicy<W> for NoRelief {
+    fn relieve(&self, worry_level: W) -> Result<W, String> {
+        Ok(worry_level)
+    }
+}
+
+impl<W, F> WorryPolicy<W> for F
+where
+    F: Fn(W) -> W,
+{
+    fn relieve(&self, worry_level: W) -> Result<W, String> {
+        Ok(self(worry_level))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RoundSnapshot<W = i64> {
+    pub round: usize,
+    pub inspection_counts: Vec<usize>,
+    pub item_positions: Vec<Vec<W>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct MonkeyTroop<W = i64> {
+    monkeys: Vec<Monkey<W>>,
+    rounds_completed: usize,
+}
+
+impl<W: Worry> MonkeyTroop<W> {
+    #[cfg(test)]
+    pub fn rounds_completed(&self) -> usize {
+        self.rounds_completed
+    }
+
+    pub fn lcm_of_divisors(&self) -> i64 {
+        self.monkeys
+            .iter()
+            .fold(1, |acc, monkey| lcm(acc, monkey.must_divide_by()))
+    }
+
+    pub fn inspection_counts(&self) -> Vec<usize> {
+        self.monkeys
+            .iter()
+            .map(|monkey| monkey.number_of_items_inspected)
+            .collect()
+    }
+
+    pub fn item_positions(&self) -> Vec<Vec<W>> {
+        self.monkeys.iter().map(|monkey| monkey.items()).collect()
+    }
+
+    pub fn snapshot(&self) -> RoundSnapshot<W> {
+        RoundSnapshot {
+            round: self.rounds_completed,
+            inspection_counts: self.inspection_counts(),
+            item_positions: self.item_positions(),
+        }
+    }
+
+    pub fn run_round<P: WorryPolicy<W>>(&mut self, policy: &P) -> Result<RoundSnapshot<W>, String> {
+        let round = self.rounds_completed + 1;
+
+        for monkey_id in 0..self.monkeys.len() {
+            let throws = self.monkeys[monkey_id]
+                .take_turn(|worry_level| policy.relieve(worry_level))
+                .map_err(|error| format!("Round {}: {}", round, error))?;
+
+            for (next_monkey_id, worry_value) in throws {
+                self.monkeys[next_monkey_id].take_item(worry_value);
+            }
+        }
+
+        self.rounds_completed = round;
+
+        Ok(self.snapshot())
+    }
+
+    pub fn run<P: WorryPolicy<W>>(&mut self, rounds: usize, policy: &P) -> Result<(), String> {
+        for _ in 0..rounds {
+            self.run_round(policy)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn run_with_snapshots<P: WorryPolicy<W>>(
+        &mut self,
+        rounds: usize,
+        policy: &P,
+    ) -> Result<Vec<RoundSnapshot<W>>, String> {
+        (0..rounds).map(|_| self.run_round(policy)).collect()
+    }
+
+    // The product of the inspection counts of the `top_k` busiest monkeys.
+    pub fn monkey_business(&self, top_k: usize) -> usize {
+        let mut inspection_counts = self.inspection_counts();
+
+        inspection_counts.sort_by(|a, b| b.cmp(a));
+
+        inspection_counts.iter().take(top_k).product()
+    }
+
+    // The throw graph in Graphviz DOT format, with an edge for each outcome of a monkey's test.
+    #[cfg(test)]
+    pub fn to_dot(&self) -> String {
+        let mut result = vec![String::from("digraph monkeys {")];
+
+        for monkey in &self.monkeys {
+            let (if_true, if_false) = monkey.throw_targets();
+
+            result.push(format!(
+                "    {} [label=\"Monkey {}\\nnew = {}\\ndivisible by {}\\ninspected {}\"];",
+                monkey.id,
+                monkey.id,
+                monkey.operation(),
+                monkey.must_divide_by(),
+                monkey.number_of_items_inspected
+            ));
+            result.push(format!(
+                "    {} -> {} [label=\"true\"];",
+                monkey.id, if_true
+            ));
+            result.push(format!(
+                "    {} -> {} [label=\"false\", style=dashed];",
+                monkey.id, if_false
+            ));
+        }
+
+        result.push(String::from("}"));
+
+        result.join("\n")
+    }
+
+    pub fn try_map_worry<V, F>(self, convert: F) -> Result<MonkeyTroop<V>, String>
+    where
+        F: Fn(&W) -> Result<V, String>,
+    {
+        Ok(MonkeyTroop {
+            monkeys: self
+                .monkeys
+                .into_iter()
+                .map(|monkey| monkey.try_map_worry(&convert))
+                .collect::<Result<_, _>>()?,
+            rounds_completed: self.rounds_completed,
+        })
+    }
+}
+
+impl MonkeyTroop {
+    pub fn parse(input: &[String]) -> Result<MonkeyTroop, String> {
+        let mut monkeys: Vec<Monkey> = input
+            .split(|line| line.trim().is_empty())
+            .filter(|lines| !lines.is_empty())
+            .map(Monkey::parse)
+            .collect::<Result<_, _>>()?;
+
+        monkeys.sort_by_key(|monkey| monkey.id);
+
+        validate(&monkeys)?;
+
+        Ok(MonkeyTroop {
+            monkeys,
+            rounds_completed: 0,
+        })
+    }
+
+    pub fn into_big_uint(self) -> Result<MonkeyTroop<BigUint>, String> {
+        self.try_map_worry(|&worry_level| {
+            u64::try_from(worry_level)
+                .map(BigUint::from)
+                .map_err(|_| format!("Worry level {} is negative", worry_level))
+        })
+    }
+
+    // One residue per monkey divisor, which is exactly what every monkey's test needs.
+    pub fn into_residues(self) -> MonkeyTroop<Residues> {
+        let moduli: Vec<i64> = self
+            .monkeys
+            .iter()
+            .map(|monkey| monkey.must_divide_by())
+            .collect();
+
+        self.try_map_worry(|&worry_level| Ok(Residues::new(worry_level, &moduli)))
+            .expect("Every worry level has residues")
+    }
+
+    pub fn item_trajectories(&self) -> Result<Vec<ItemTrajectory>, String> {
+        let policy = ModuloBy(self.lcm_of_divisors());
+
+        self.item_positions()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(monkey, items)| {
+                items.into_iter().map(move |worry_level| {
+                    policy.relieve(worry_level).map(|worry_level| ItemState {
+                        monkey,
+                        worry_level,
+                    })
+                })
+            })
+            .map(|start| ItemTrajectory::trace(start?, |state| self.advance_item(state, &policy)))
+            .collect()
+    }
+
+    // Inspection counts after `rounds` more rounds under modular worry, without simulating them.
+    pub fn extrapolate_inspection_counts(&self, rounds: u64) -> Result<Vec<u64>, String> {
+        let mut result: Vec<u64> = self
+            .inspection_counts()
+            .iter()
+            .map(|&count| count as u64)
+            .collect();
+
+        for trajectory in self.item_trajectories()? {
+            let inspections = trajectory.inspections_by_monkey(rounds, self.monkeys.len());
+
+            for (count, extra) in result.iter_mut().zip(inspections) {
+                *count += extra;
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn extrapolate_monkey_business(&self, rounds: u64, top_k: usize) -> Result<u128, String> {
+        let mut inspection_counts = self.extrapolate_inspection_counts(rounds)?;
+
+        inspection_counts.sort_by(|a, b| b.cmp(a));
+
+        Ok(inspection_counts
+            .iter()
+            .take(top_k)
+            .map(|&count| count as u128)
+            .product())
+    }
+
+    /*
+    One round for a single item. Monkeys take their turns in order, so an item thrown to
+    a monkey further along is inspected again in the same round.
+    */
+    fn advance_item(
+        &self,
+        state: ItemState,
+        policy: &ModuloBy,
+    ) -> Result<(ItemState, Vec<usize>), String> {
+        let mut inspected_by = vec![];
+        let mut current = state;
+
+        loop {
+            let (next_monkey, worry_level) = self.monkeys[current.monkey]
+                .inspect(&current.worry_level, |worry_level| {
+                    policy.relieve(worry_level)
+                })?;
+
+            inspected_by.push(current.monkey);
+
+            let thrown_forward = next_monkey > current.monkey;
+
+            current = ItemState {
+                monkey: next_monkey,
+                worry_level,
+            };
+
+            if !thrown_forward {
+                return Ok((current, inspected_by));
+            }
+        }
+    }
+}
+
+impl From<&[String]> for MonkeyTroop {
+    fn from(input: &[String]) -> MonkeyTroop {
+        MonkeyTroop::parse(input).unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+// Expects the monkeys sorted by id.
+fn validate(monkeys: &[Monkey]) -> Result<(), String> {
+    for (index, monkey) in monkeys.iter().enumerate() {
+        if index > 0 && monkeys[index - 1].id == monkey.id {
+            return Err(format!("Monkey {} is defined more than once", monkey.id));
+        }
+
+        if monkey.id != index {
+            return Err(format!(
+                "Monkey ids must count up from 0, but monkey {} is missing",
+                index
+            ));
+        }
+    }
+
+    for monkey in monkeys {
+        let (if_true, if_false) = monkey.throw_targets();
+
+        for target in [if_true, if_false] {
+            if target == monkey.id {
+                return Err(format!("Monkey {} throws to itself", monkey.id));
+            }
+
+            if target >= monkeys.len() {
+                return Err(format!(
+                    "Monkey {} throws to monkey {}, which doesn't exist",
+                    monkey.id, target
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::file_reader::to_string_vector;
+
+    fn get_troop() -> MonkeyTroop {
+        let input = to_string_vector("test_inputs/day_11_part_1.txt").unwrap();
+
+        MonkeyTroop::from(input.as_slice())
+    }
+
+    #[test]
+    fn test_run_round_snapshot() {
+        let mut troop = get_troop();
+
+        let expected = RoundSnapshot {
+            round: 1,
+            inspection_counts: vec![2, 4, 3, 5],
+            item_positions: vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![],
+            ],
+        };
+
+        let result = troop.run_round(&DivideBy(3)).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_run_with_snapshots() {
+        let mut troop = get_troop();
+
+        let snapshots = troop.run_with_snapshots(20, &DivideBy(3)).unwrap();
+
+        let expected = vec![101, 95, 7, 105];
+
+        let result = snapshots.last().unwrap().inspection_counts.clone();
+
+        assert_eq!(result, expected);
+        assert_eq!(snapshots.len(), 20);
+        assert_eq!(troop.rounds_completed(), 20);
+    }
+
+    #[test]
+    fn test_monkey_business() {
+        let mut troop = get_troop();
+
+        troop.run(20, &DivideBy(3)).unwrap();
+
+        let expected = vec![1, 105, 105 * 101, 105 * 101 * 95];
+
+        let result: Vec<usize> = (0..4).map(|top_k| troop.monkey_business(top_k)).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_modulo_policy() {
+        let mut troop = get_troop();
+
+        let policy = ModuloBy(troop.lcm_of_divisors());
+
+        troop.run(1_000, &policy).unwrap();
+
+        let expected = vec![5_204, 4_792, 199, 5_192];
+
+        let result = troop.inspection_counts();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_closure_policy() {
+        let mut troop = get_troop();
+        let mut other_troop = get_troop();
+
+        troop.run(20, &|worry_level: i64| worry_level / 3).unwrap();
+        other_troop.run(20, &DivideBy(3)).unwrap();
+
+        assert_eq!(troop, other_troop);
+    }
+
+    #[test]
+    fn test_checked_overflow_reports_monkey_and_round() {
+        let mut troop = get_troop();
+
+        let expected = Err(String::from(
+            "Round 11: Monkey 2: 3740147530 * 3740147530 overflows",
+        ));
+
+        let result = troop.run(20, &NoRelief);
+
+        assert_eq!(result, expected);
+        assert_eq!(troop.rounds_completed(), 10);
+    }
+
+    #[test]
+    fn test_big_uint_matches_i64() {
+        let mut troop = get_troop();
+        let mut big_troop = get_troop().into_big_uint().unwrap();
+
+        troop.run(20, &DivideBy(3)).unwrap();
+        big_troop.run(20, &DivideBy(3)).unwrap();
+
+        assert_eq!(big_troop.inspection_counts(), troop.inspection_counts());
+    }
+
+    #[test]
+    fn test_relief_rejects_invalid_divisors() {
+        let expected = vec![
+            Err(String::from("Can't divide worry level 7 by 0")),
+            Err(String::from("Can't take worry level 7 modulo 0")),
+            Err(String::from("Can't divide worry level 7 by -3")),
+            Err(String::from("Can't take worry level 7 modulo -3")),
+        ];
+
+        let result = vec![
+            DivideBy(0).relieve(7),
+            ModuloBy(0).relieve(7),
+            DivideBy(-3).relieve(BigUint::from(7)).map(|_| 0),
+            ModuloBy(-3).relieve(BigUint::from(7)).map(|_| 0),
+        ];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_run_reports_relief_errors() {
+        let mut troop = get_troop().into_big_uint().unwrap();
+
+        let expected = Err(String::from(
+            "Round 1: Monkey 0: Can't divide worry level 1501 by 0",
+        ));
+
+        let result = troop.run(1, &DivideBy(0));
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_residues_match_big_uint_without_relief() {
+        let mut big_troop = get_troop().into_big_uint().unwrap();
+        let mut residue_troop = get_troop().into_residues();
+
+        big_troop.run(8, &NoRelief).unwrap();
+        residue_troop.run(8, &NoRelief).unwrap();
+
+        assert_eq!(
+            residue_troop.inspection_counts(),
+            big_troop.inspection_counts()
+        );
+    }
+
+    #[test]
+    fn test_residues_part_2() {
+        let mut troop = get_troop().into_residues();
+
+        troop.run(10_000, &NoRelief).unwrap();
+
+        let expected = 2_713_310_158;
+
+        let result = troop.monkey_business(2);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_extrapolate_matches_simulation() {
+        let mut troop = get_troop();
+
+        let expected: Vec<u64> = vec![52_166, 47_830, 1_938, 52_013];
+
+        let result = troop.extrapolate_inspection_counts(10_000).unwrap();
+
+        troop
+            .run(10_000, &ModuloBy(troop.lcm_of_divisors()))
+            .unwrap();
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            troop.inspection_counts(),
+            expected
+                .iter()
+                .map(|&count| count as usize)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_extrapolate_after_simulated_rounds() {
+        let mut troop = get_troop();
+
+        let expected = troop
+            .extrapolate_inspection_counts(1_000_000_000_000)
+            .unwrap();
+
+        troop
+            .run(1_234, &ModuloBy(troop.lcm_of_divisors()))
+            .unwrap();
+
+        let result = troop
+            .extrapolate_inspection_counts(1_000_000_000_000 - 1_234)
+            .unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_extrapolate_monkey_business() {
+        let troop = get_troop();
+
+        let expected = 2_713_310_158;
+
+        let result = troop.extrapolate_monkey_business(10_000, 2).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_validates_troop() {
+        let input = to_string_vector("test_inputs/day_11_part_1.txt").unwrap();
+
+        let replacements = [
+            ("Monkey 3:", "Monkey 4:"),
+            ("Monkey 3:", "Monkey 2:"),
+            ("throw to monkey 0", "throw to monkey 7"),
+            ("throw to monkey 1\n", "throw to monkey 2\n"),
+            ("divisible by 13", "divisible by -13"),
+        ];
+
+        let expected = vec![
+            Err(String::from(
+                "Monkey ids must count up from 0, but monkey 3 is missing",
+            )),
+            Err(String::from("Monkey 2 is defined more than once")),
+            Err(String::from(
+                "Monkey 1 throws to monkey 7, which doesn't exist",
+            )),
+            Err(String::from("Monkey 2 throws to itself")),
+            Err(String::from("Monkey 2: can't test for divisibility by -13")),
+        ];
+
+        let result: Vec<Result<MonkeyTroop, String>> = replacements
+            .iter()
+            .map(|(from, to)| {
+                let lines: Vec<String> = input
+                    .join("\n")
+                    .replacen(from, to, 1)
+                    .lines()
+                    .map(String::from)
+                    .collect();
+
+                MonkeyTroop::parse(&lines)
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_parse_ignores_trailing_blank_lines() {
+        let mut input = to_string_vector("test_inputs/day_11_part_1.txt").unwrap();
+
+        input.push(String::from(""));
+        input.push(String::from(""));
+
+        let expected = get_troop();
+
+        let result = MonkeyTroop::parse(&input);
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut troop = get_troop();
+
+        troop.run(1, &DivideBy(3)).unwrap();
+
+        let expected = [
+            "digraph monkeys {",
+            "    0 [label=\"Monkey 0\\nnew = old * 19\\ndivisible by 23\\ninspected 2\"];",
+            "    0 -> 2 [label=\"true\"];",
+            "    0 -> 3 [label=\"false\", style=dashed];",
+            "    1 [label=\"Monkey 1\\nnew = old + 6\\ndivisible by 19\\ninspected 4\"];",
+            "    1 -> 2 [label=\"true\"];",
+            "    1 -> 0 [label=\"false\", style=dashed];",
+            "    2 [label=\"Monkey 2\\nnew = old * old\\ndivisible by 13\\ninspected 3\"];",
+            "    2 -> 1 [label=\"true\"];",
+            "    2 -> 3 [label=\"false\", style=dashed];",
+            "    3 [label=\"Monkey 3\\nnew = old + 3\\ndivisible by 17\\ninspected 5\"];",
+            "    3 -> 0 [label=\"true\"];",
+            "    3 -> 1 [label=\"false\", style=dashed];",
+            "}",
+        ]
+        .join("\n");
+
+        let result = troop.to_dot();
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+
+use crate::util::big_uint::BigUint;
+
+use super::expression::Operator;
+
+/*
+A worry level a monkey can hold. Arithmetic is checked, so a worry level that can't
+be represented is reported instead of silently wrapping around.
+*/
+pub trait Worry: Clone + fmt::Debug + fmt::Display + PartialEq {
+    // A constant from an operation, represented the same way as `self`.
+    fn lift(&self, value: i64) -> Self;
+
+    fn checked_apply(&self, operator: Operator, other: &Self) -> Result<Self, String>;
+
+    fn is_divisible_by(&self, divisor: i64) -> bool;
+}
+
+impl Worry for i64 {
+    fn lift(&self, value: i64) -> i64 {
+        value
+    }
+
+    fn checked_apply(&self, operator: Operator, other: &i64) -> Result<i64, String> {
+        if operator.divides() && (*other == 0) {
+            return Err(division_by_zero(self, operator, other));
+        }
+
+        let result = match operator {
+            Operator::Add => self.checked_add(*other),
+            Operator::Subtract => self.checked_sub(*other),
+            Operator::Multiply => self.checked_mul(*other),
+            Operator::Divide => self.checked_div(*other),
+            Operator::Remainder => self.checked_rem(*other),
+        };
+
+        result.ok_or_else(|| format!("{} {} {} overflows", self, operator.symbol(), other))
+    }
+
+    fn is_divisible_by(&self, divisor: i64) -> bool {
+        self % divisor == 0
+    }
+}
+
+impl Worry for BigUint {
+    fn lift(&self, value: i64) -> BigUint {
+        BigUint::from(u64::try_from(value).expect("Operation constants are never negative"))
+    }
+
+    fn checked_apply(&self, operator: Operator, other: &BigUint) -> Result<BigUint, String> {
+        if operator.divides() && other.is_zero() {
+            return Err(division_by_zero(self, operator, other));
+        }
+
+        let result = match operator {
+            Operator::Add => Some(self + other),
+            Operator::Subtract => self.checked_sub(other),
+            Operator::Multiply => Some(self * other),
+            Operator::Divide => self.div_rem(other).map(|(quotient, _)| quotient),
+            Operator::Remainder => self.div_rem(other).map(|(_, remainder)| remainder),
+        };
+
+        result.ok_or_else(|| {
+            format!(
+                "{} {} {} has no unsigned result",
+                self,
+                operator.symbol(),
+                other
+            )
+        })
+    }
+
+    fn is_divisible_by(&self, divisor: i64) -> bool {
+        let divisor = u64::try_from(divisor).expect("Divisors are never negative");
+
+        self.div_rem_u64(divisor)
+            .is_some_and(|(_, remainder)| remainder == 0)
+    }
+}
+
+fn division_by_zero<W: Worry>(left: &W, operator: Operator, right: &W) -> String {
+    format!(
+        "{} {} {} is a division by zero",
+        left,
+        operator.symbol(),
+        right
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_i64_checked_apply_overflow() {
+        let expected = Err(String::from("9223372036854775807 * 2 overflows"));
+
+        let result = i64::MAX.checked_apply(Operator::Multiply, &2);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_big_uint_checked_apply() {
+        let worry_level = BigUint::from(i64::MAX as u64);
+
+        let expected = "18446744073709551614";
+
+        let result = worry_level
+            .checked_apply(Operator::Multiply, &worry_level.lift(2))
+            .unwrap()
+            .to_string();
+
+        assert_eq!(result, expected);
+        assert!(worry_level
+            .checked_apply(Operator::Subtract, &worry_level.lift(i64::MAX))
+            .unwrap()
+            .is_zero());
+    }
+
+    #[test]
+    fn test_checked_apply_division_by_zero() {
+        let expected = vec![
+            Err(String::from("7 / 0 is a division by zero")),
+            Err(String::from("7 % 0 is a division by zero")),
+        ];
+
+        let result = vec![
+            7.checked_apply(Operator::Divide, &0),
+            7.checked_apply(Operator::Remainder, &0),
+        ];
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            BigUint::from(7).checked_apply(Operator::Divide, &BigUint::zero()),
+            Err(String::from("7 / 0 is a division by zero"))
+        );
+    }
+}
@@ -1,35 +1,87 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::HashMap;
 
+use crate::util::grid::Grid;
+#[cfg(test)]
+use crate::util::image::{export_colors, ImageFormat, Rgb};
 use crate::util::point_2d::Point2d;
-
-#[derive(Debug, PartialEq)]
-struct Move {
-    point: Point2d<i32>,
-    distance_traveled: usize,
-    points_seen: Vec<Point2d<i32>>,
+use crate::util::search::dijkstra;
+
+use super::movement::MovementRules;
+
+/*
+How far every cell is from the end, found with one search backwards from the end. Each
+cell also remembers its first step towards the end, so paths can be read straight off.
+*/
+#[derive(Debug, PartialEq, Clone)]
+pub struct DistanceMap {
+    distances: Grid<Option<usize>>,
+    next_steps: Grid<Option<Point2d<i32>>>,
+    closest_by_height: HashMap<char, (Point2d<i32>, usize)>,
 }
 
-impl Move {
-    fn new(point: &Point2d<i32>) -> Move {
-        Move {
-            point: *point,
-            distance_traveled: 0,
-            points_seen: vec![],
-        }
+impl DistanceMap {
+    #[cfg(test)]
+    pub fn distances(&self) -> &Grid<Option<usize>> {
+        &self.distances
     }
 
-    fn next_move(&self, x_delta: i32, y_delta: i32) -> Move {
-        let distance_traveled =
-            self.distance_traveled + (x_delta.abs() as usize) + (y_delta.abs() as usize);
-        let mut points_seen: Vec<Point2d<i32>> = self.points_seen.iter().cloned().collect();
+    pub fn distance_from(&self, point: &Point2d<i32>) -> Option<usize> {
+        self.distances.get(point).cloned().flatten()
+    }
+
+    pub fn path_from(&self, point: &Point2d<i32>) -> Option<Vec<Point2d<i32>>> {
+        self.distance_from(point)?;
 
-        points_seen.push(self.point);
+        let mut result = vec![*point];
 
-        Move {
-            point: self.point.add_t((x_delta, y_delta)),
-            distance_traveled,
-            points_seen,
+        while let Some(next_step) = self
+            .next_steps
+            .get(result.last().unwrap())
+            .cloned()
+            .flatten()
+        {
+            result.push(next_step);
         }
+
+        Some(result)
+    }
+
+    // The first cell in reading order among those of `height` closest to the end.
+    pub fn closest_with_height(&self, height: char) -> Option<(Point2d<i32>, usize)> {
+        self.closest_by_height.get(&height).cloned()
+    }
+
+    // Red next to the end fading to blue furthest away, with unreachable cells black.
+    #[cfg(test)]
+    pub fn heat_map(&self) -> Vec<Vec<Rgb>> {
+        let furthest = self
+            .distances
+            .iter()
+            .filter_map(|(_, distance)| *distance)
+            .max();
+
+        self.distances
+            .rows()
+            .take(self.distances.height())
+            .map(|row| {
+                row.iter()
+                    .map(|distance| match (distance, furthest) {
+                        (Some(distance), Some(furthest)) if furthest > 0 => {
+                            let warmth = (255 * (furthest - distance) / furthest) as u8;
+
+                            Rgb::new(warmth, 0, 255 - warmth)
+                        }
+                        (Some(_), _) => Rgb::new(255, 0, 0),
+                        (None, _) => Rgb::new(0, 0, 0),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub fn export_heat_map(&self, format: ImageFormat, scale: usize) -> Vec<u8> {
+        export_colors(&self.heat_map(), format, scale)
     }
 }
 
@@ -37,136 +89,183 @@ impl Move {
 pub struct HeightMap {
     pub start: Point2d<i32>,
     pub end: Point2d<i32>,
-    points: HashMap<Point2d<i32>, char>,
+    heights: Grid<char>,
+    rules: MovementRules,
 }
 
 impl From<&Vec<String>> for HeightMap {
     fn from(input: &Vec<String>) -> HeightMap {
-        let mut result = HeightMap::new();
-
-        for (row_index, row) in input.iter().enumerate() {
-            for (col_index, col) in row.chars().enumerate() {
-                let point = Point2d::new(col_index as i32, row_index as i32);
-
-                match col {
-                    'S' => {
-                        result.start = point;
-                        result.points.insert(point, 'a');
-                    }
-                    'E' => {
-                        result.end = point;
-                        result.points.insert(point, 'z');
-                    }
-                    _ => {
-                        result.points.insert(point, col);
-                    }
-                }
-            }
-        }
-
-        result
+        HeightMap::parse(input, MovementRules::default())
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 }
 
 impl HeightMap {
-    fn new() -> HeightMap {
-        HeightMap {
-            start: Point2d::new(0, 0),
-            end: Point2d::new(0, 0),
-            points: HashMap::new(),
-        }
+    pub fn parse(input: &[String], rules: MovementRules) -> Result<HeightMap, String> {
+        let mut heights = Grid::parse(input, |c| {
+            (c.is_ascii_lowercase() || c == 'S' || c == 'E').then_some(c)
+        })?;
+
+        let start = heights
+            .position(|height| *height == 'S')
+            .ok_or_else(|| String::from("The height map has no start `S`"))?;
+        let end = heights
+            .position(|height| *height == 'E')
+            .ok_or_else(|| String::from("The height map has no end `E`"))?;
+
+        heights.set(&start, rules.start_height);
+        heights.set(&end, rules.end_height);
+
+        Ok(HeightMap {
+            start,
+            end,
+            heights,
+            rules,
+        })
     }
 
     pub fn get_shortest_hiking_path(&self) -> Vec<Point2d<i32>> {
-        let lowest_points: Vec<Point2d<i32>> = self
-            .points
-            .iter()
-            .filter(|(_, height)| **height == 'a')
-            .map(|(point, _)| *point)
-            .collect();
+        let distances = self.distances_to_end();
+
+        distances
+            .closest_with_height(self.rules.start_height)
+            .and_then(|(start, _)| distances.path_from(&start))
+            .unwrap_or_default()
+    }
 
-        let mut min_path_so_far = vec![];
-        let mut already_started = HashSet::new();
+    pub fn get_shortest_path(&self) -> Vec<Point2d<i32>> {
+        self.get_shortest_path_from(&[self.start])
+    }
 
-        for start in lowest_points.into_iter() {
-            let path = self.get_shortest_path_starting_from(
-                start,
-                min_path_so_far.len(),
-                &already_started,
-            );
+    // The cheapest path to the end from whichever of `starts` is closest, or empty if none reach it.
+    pub fn get_shortest_path_from(&self, starts: &[Point2d<i32>]) -> Vec<Point2d<i32>> {
+        dijkstra(
+            starts.iter().cloned(),
+            |point| self.reachable_from(*point),
+            |point| *point == self.end,
+        )
+        .path_to_goal()
+        .unwrap_or_default()
+    }
 
-            if path.is_empty() {
-                continue;
-            }
+    #[cfg(test)]
+    pub fn render(&self) -> String {
+        let mut heights = self.heights.clone();
 
-            if min_path_so_far.is_empty() || (path.len() < min_path_so_far.len()) {
-                min_path_so_far = path;
-            }
+        heights.set(&self.start, 'S');
+        heights.set(&self.end, 'E');
 
-            already_started.insert(start);
+        heights.render(|height| *height)
+    }
+
+    /*
+    Draws each step of `path` as an arrow pointing to the next cell, like the puzzle's
+    illustrations, with the end as `E` and everything else as `.`. Diagonal steps are
+    drawn as `/` or `\\`.
+    */
+    #[cfg(test)]
+    pub fn render_path(&self, path: &[Point2d<i32>]) -> String {
+        let mut result = self.heights.map(|_| '.');
+
+        for step in path.windows(2) {
+            let arrow = match (step[1].x - step[0].x, step[1].y - step[0].y) {
+                (0, -1) => '^',
+                (1, 0) => '>',
+                (0, 1) => 'v',
+                (-1, 0) => '<',
+                (1, -1) | (-1, 1) => '/',
+                _ => '\\',
+            };
+
+            result.set(&step[0], arrow);
+        }
+
+        if let Some(last) = path.last() {
+            result.set(last, if *last == self.end { 'E' } else { '*' });
         }
 
-        min_path_so_far
+        result.render(|c| *c)
     }
 
-    pub fn get_shortest_path(&self) -> Vec<Point2d<i32>> {
-        self.get_shortest_path_starting_from(self.start, self.points.len(), &HashSet::new())
+    pub fn path_cost(&self, path: &[Point2d<i32>]) -> usize {
+        path.windows(2)
+            .map(|step| self.rules.cost(self.height_difference(&step[0], &step[1])))
+            .sum()
     }
 
-    pub fn get_shortest_path_starting_from(
-        &self,
-        start: Point2d<i32>,
-        max_distance: usize,
-        already_started_points: &HashSet<Point2d<i32>>,
-    ) -> Vec<Point2d<i32>> {
-        let mut added_points: HashSet<Point2d<i32>> = HashSet::from([start]);
-        let mut move_queue: VecDeque<Move> = VecDeque::new();
-        let mut result = vec![];
+    pub fn distances_to_end(&self) -> DistanceMap {
+        let search = dijkstra([self.end], |point| self.reachable_to(*point), |_| false);
 
-        let first_move = Move::new(&start);
+        let mut distances = Grid::new(self.heights.width(), self.heights.height(), None);
 
-        move_queue.push_back(first_move);
-        added_points.extend(already_started_points);
+        for (point, distance) in search.costs() {
+            distances.set(point, Some(*distance));
+        }
 
-        while let Some(current_move) = move_queue.pop_front() {
-            if current_move.point == self.end {
-                result = current_move.points_seen;
-                result.push(self.end);
+        let distance_at = |point: &Point2d<i32>| distances.get(point).cloned().flatten();
 
-                break;
-            }
+        let mut next_steps = Grid::new(self.heights.width(), self.heights.height(), None);
+        let mut closest_by_height: HashMap<char, (Point2d<i32>, usize)> = HashMap::new();
 
-            if (current_move.distance_traveled == max_distance) && (max_distance != 0) {
-                result = vec![];
+        for (point, height) in self.heights.iter() {
+            let Some(distance) = distance_at(&point) else {
+                continue;
+            };
+
+            // Searching backwards, a cell was reached from its next step towards the end.
+            next_steps.set(&point, search.predecessor(&point).cloned());
 
-                break;
+            if closest_by_height
+                .get(height)
+                .is_none_or(|(_, closest)| distance < *closest)
+            {
+                closest_by_height.insert(*height, (point, distance));
             }
+        }
+
+        DistanceMap {
+            distances,
+            next_steps,
+            closest_by_height,
+        }
+    }
+
+    fn neighbours(&self, point: &Point2d<i32>) -> Vec<Point2d<i32>> {
+        if self.rules.diagonal {
+            self.heights.neighbours_8(point).collect()
+        } else {
+            self.heights.neighbours_4(point).collect()
+        }
+    }
 
-            let mut next_moves: Vec<Move> = vec![
-                current_move.next_move(0, -1),
-                current_move.next_move(1, 0),
-                current_move.next_move(0, 1),
-                current_move.next_move(-1, 0),
-            ]
+    // Left and down first, which breaks ties the same way as the puzzle's example paths.
+    fn reachable_from(&self, point: Point2d<i32>) -> Vec<(Point2d<i32>, usize)> {
+        let mut result: Vec<(Point2d<i32>, usize)> = self
+            .neighbours(&point)
             .into_iter()
-            .filter(|next_move| self.points.contains_key(&next_move.point))
-            .filter(|next_move| !added_points.contains(&next_move.point))
-            .filter(|next_move| self.height_difference(&current_move.point, &next_move.point) <= 1)
+            .map(|next| (next, self.height_difference(&point, &next)))
+            .filter(|(_, height_change)| self.rules.allows(*height_change))
+            .map(|(next, height_change)| (next, self.rules.cost(height_change)))
             .collect();
 
-            while let Some(next_move) = next_moves.pop() {
-                added_points.insert(next_move.point);
-
-                move_queue.push_back(next_move);
-            }
-        }
+        result.reverse();
 
         result
     }
 
+    // The cells a single step can come from, for searching backwards from the end.
+    fn reachable_to(&self, point: Point2d<i32>) -> Vec<(Point2d<i32>, usize)> {
+        self.neighbours(&point)
+            .into_iter()
+            .map(|previous| (previous, self.height_difference(&previous, &point)))
+            .filter(|(_, height_change)| self.rules.allows(*height_change))
+            .map(|(previous, height_change)| (previous, self.rules.cost(height_change)))
+            .collect()
+    }
+
     fn height_difference(&self, current_point: &Point2d<i32>, next_point: &Point2d<i32>) -> i32 {
-        let lhs = self.points.get(current_point).map_or(0, |v| *v as i32);
-        let rhs = self.points.get(next_point).map_or(0, |v| *v as i32);
+        let lhs = self.heights.get(current_point).map_or(0, |v| *v as i32);
+        let rhs = self.heights.get(next_point).map_or(0, |v| *v as i32);
 
         rhs - lhs
     }
@@ -176,20 +275,7 @@ impl HeightMap {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_move_next() {
-        let current_move = Move::new(&Point2d::new(0, 0));
-
-        let expected = Move {
-            point: Point2d::new(1, -1),
-            distance_traveled: 2,
-            points_seen: vec![Point2d::new(0, 0), Point2d::new(0, -1)],
-        };
-
-        let result = current_move.next_move(0, -1).next_move(1, 0);
-
-        assert_eq!(result, expected);
-    }
+    use crate::day_12::movement::StepCost;
 
     #[test]
     fn test_from() {
@@ -205,7 +291,7 @@ mod tests {
 
         assert_eq!(result.start, Point2d::new(0, 0));
         assert_eq!(result.end, Point2d::new(5, 2));
-        assert_eq!(result.points.len(), 40);
+        assert_eq!(result.heights.len(), 40);
     }
 
     #[test]
@@ -344,4 +430,297 @@ mod tests {
         assert_eq!(result_2, expected_2);
         assert_eq!(result_3, expected_3);
     }
+
+    #[test]
+    fn test_distances_to_end() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let height_map = HeightMap::from(&input);
+
+        let distances = height_map.distances_to_end();
+
+        let expected = vec![Some(31), Some(0), Some(29), Some(30)];
+
+        let result: Vec<Option<usize>> = [(0, 0), (5, 2), (0, 4), (0, 3)]
+            .iter()
+            .map(|&point| distances.distance_from(&Point2d::from(point)))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(distances.distance_from(&Point2d::new(8, 0)), None);
+        assert_eq!(
+            distances
+                .distances()
+                .iter()
+                .filter(|(_, d)| d.is_some())
+                .count(),
+            40
+        );
+    }
+
+    #[test]
+    fn test_closest_with_height() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let distances = HeightMap::from(&input).distances_to_end();
+
+        let expected = vec![
+            Some((Point2d::new(0, 4), 29)),
+            Some((Point2d::new(1, 4), 28)),
+            Some((Point2d::new(5, 2), 0)),
+            None,
+        ];
+
+        let result: Vec<Option<(Point2d<i32>, usize)>> = ['a', 'b', 'z', 'S']
+            .iter()
+            .map(|&height| distances.closest_with_height(height))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_path_from_matches_distances() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let height_map = HeightMap::from(&input);
+        let distances = height_map.distances_to_end();
+
+        let path = distances.path_from(&height_map.start).unwrap();
+
+        let expected: Vec<usize> = (0..height_map.get_shortest_path().len()).rev().collect();
+
+        let result: Vec<usize> = path
+            .iter()
+            .map(|point| distances.distance_from(point).unwrap())
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(path.last(), Some(&height_map.end));
+    }
+
+    #[test]
+    fn test_path_from_with_free_steps() {
+        let input = [String::from("SaaE")];
+        let rules = MovementRules {
+            max_climb: None,
+            step_cost: StepCost {
+                base: 0,
+                per_climb: 1,
+                per_descent: 0,
+            },
+            ..MovementRules::default()
+        };
+
+        let height_map = HeightMap::parse(&input, rules).unwrap();
+        let distances = height_map.distances_to_end();
+
+        let expected = Some(vec![
+            Point2d::new(0, 0),
+            Point2d::new(1, 0),
+            Point2d::new(2, 0),
+            Point2d::new(3, 0),
+        ]);
+
+        let result = distances.path_from(&height_map.start);
+
+        assert_eq!(result, expected);
+        assert_eq!(distances.distance_from(&height_map.start), Some(25));
+        assert_eq!(
+            height_map.get_shortest_hiking_path().last(),
+            Some(&height_map.end)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = [
+            vec![String::from("Sab"), String::from("a?E")],
+            vec![String::from("Sab"), String::from("abc")],
+        ];
+
+        let expected = vec![
+            Err(String::from("Unexpected '?' at (1, 1)")),
+            Err(String::from("The height map has no end `E`")),
+        ];
+
+        let result: Vec<Result<HeightMap, String>> = input
+            .iter()
+            .map(|lines| HeightMap::parse(lines, MovementRules::default()))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_diagonal_movement() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let rules = MovementRules {
+            diagonal: true,
+            ..MovementRules::default()
+        };
+
+        let height_map = HeightMap::parse(&input, rules).unwrap();
+
+        let expected = 27;
+
+        let result = height_map.get_shortest_path().len() - 1;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_limited_descent() {
+        let input = vec![String::from("Sdcba"), String::from("aaaaE")];
+
+        let rules = MovementRules {
+            max_climb: None,
+            max_descent: Some(0),
+            ..MovementRules::default()
+        };
+
+        let height_map = HeightMap::parse(&input, rules).unwrap();
+
+        let expected = vec![
+            Point2d::new(0, 0),
+            Point2d::new(0, 1),
+            Point2d::new(1, 1),
+            Point2d::new(2, 1),
+            Point2d::new(3, 1),
+            Point2d::new(4, 1),
+        ];
+
+        let result = height_map.get_shortest_path();
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            height_map
+                .distances_to_end()
+                .distance_from(&Point2d::new(1, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_height_change_cost() {
+        let input = vec![String::from("Sbcdy"), String::from("aaaaE")];
+
+        let rules = MovementRules {
+            max_climb: None,
+            step_cost: StepCost {
+                base: 1,
+                per_climb: 1,
+                per_descent: 0,
+            },
+            ..MovementRules::default()
+        };
+
+        let height_map = HeightMap::parse(&input, rules).unwrap();
+
+        let path = height_map.get_shortest_path();
+
+        let expected = (30, 30);
+
+        let result = (
+            height_map.path_cost(&path),
+            height_map
+                .distances_to_end()
+                .distance_from(&height_map.start)
+                .unwrap(),
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let expected = input.join("\n");
+
+        let result = HeightMap::from(&input).render();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_render_path() {
+        let input = vec![
+            String::from("Sabqponm"),
+            String::from("abcryxxl"),
+            String::from("accszExk"),
+            String::from("acctuvwj"),
+            String::from("abdefghi"),
+        ];
+
+        let height_map = HeightMap::from(&input);
+
+        let expected = ["v..v<<<<", ">v.vv<<^", ".v.v>E^^", ".>v>>>^^", "..>>>>>^"].join("\n");
+
+        let result = height_map.render_path(&height_map.get_shortest_path());
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_heat_map() {
+        let input = vec![String::from("SbcE"), String::from("zzzz")];
+
+        let rules = MovementRules {
+            max_descent: Some(0),
+            end_height: 'd',
+            ..MovementRules::default()
+        };
+
+        let distances = HeightMap::parse(&input, rules).unwrap().distances_to_end();
+
+        let expected = vec![
+            vec![
+                Rgb::new(0, 0, 255),
+                Rgb::new(85, 0, 170),
+                Rgb::new(170, 0, 85),
+                Rgb::new(255, 0, 0),
+            ],
+            vec![Rgb::new(0, 0, 0); 4],
+        ];
+
+        let result = distances.heat_map();
+
+        assert_eq!(result, expected);
+        assert_eq!(
+            distances.export_heat_map(ImageFormat::Ppm, 1)[..11],
+            b"P6\n4 2\n255\n"[..]
+        );
+    }
 }
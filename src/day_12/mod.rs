@@ -1,4 +1,5 @@
 mod hill_climber;
+mod movement;
 
 use crate::util::file_reader::to_string_vector;
 
@@ -12,8 +13,11 @@ pub fn run() {
     let shortest_path = height_map.get_shortest_path();
     let shortest_hiking_path = height_map.get_shortest_hiking_path();
 
-    println!("Day 12, Part 1: {}", shortest_path.len() - 1);
-    println!("Day 12, Part 2: {}", shortest_hiking_path.len() - 1);
+    println!("Day 12, Part 1: {}", height_map.path_cost(&shortest_path));
+    println!(
+        "Day 12, Part 2: {}",
+        height_map.path_cost(&shortest_hiking_path)
+    );
 }
 
 #[cfg(test)]
@@ -0,0 +1,128 @@
+// A base cost for every step plus extra for every unit of height climbed or descended.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct StepCost {
+    pub base: usize,
+    pub per_climb: usize,
+    pub per_descent: usize,
+}
+
+// Every step costs one, whatever the height change.
+impl Default for StepCost {
+    fn default() -> StepCost {
+        StepCost {
+            base: 1,
+            per_climb: 0,
+            per_descent: 0,
+        }
+    }
+}
+
+/*
+How a hiker may move across the terrain. The defaults are the puzzle's rules: four
+directions, climbing at most one level, descending any amount, every step costing one,
+and `S` and `E` standing for heights `a` and `z`.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MovementRules {
+    pub max_climb: Option<i32>,
+    pub max_descent: Option<i32>,
+    pub diagonal: bool,
+    pub step_cost: StepCost,
+    pub start_height: char,
+    pub end_height: char,
+}
+
+impl Default for MovementRules {
+    fn default() -> MovementRules {
+        MovementRules {
+            max_climb: Some(1),
+            max_descent: None,
+            diagonal: false,
+            step_cost: StepCost::default(),
+            start_height: 'a',
+            end_height: 'z',
+        }
+    }
+}
+
+impl MovementRules {
+    pub fn allows(&self, height_change: i32) -> bool {
+        let climb_allowed = self
+            .max_climb
+            .is_none_or(|max_climb| height_change <= max_climb);
+        let descent_allowed = self
+            .max_descent
+            .is_none_or(|max_descent| -height_change <= max_descent);
+
+        climb_allowed && descent_allowed
+    }
+
+    pub fn cost(&self, height_change: i32) -> usize {
+        let change = height_change.unsigned_abs() as usize;
+
+        if height_change > 0 {
+            self.step_cost.base + change * self.step_cost.per_climb
+        } else {
+            self.step_cost.base + change * self.step_cost.per_descent
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_allows() {
+        let rules = MovementRules::default();
+
+        let expected = vec![true, true, true, false];
+
+        let result: Vec<bool> = [-25, 0, 1, 2]
+            .iter()
+            .map(|&height_change| rules.allows(height_change))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_limited_descent_allows() {
+        let rules = MovementRules {
+            max_climb: None,
+            max_descent: Some(2),
+            ..MovementRules::default()
+        };
+
+        let expected = vec![false, true, true, true];
+
+        let result: Vec<bool> = [-3, -2, 0, 25]
+            .iter()
+            .map(|&height_change| rules.allows(height_change))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_height_change_cost() {
+        let rules = MovementRules {
+            step_cost: StepCost {
+                base: 1,
+                per_climb: 3,
+                per_descent: 1,
+            },
+            ..MovementRules::default()
+        };
+
+        let expected = vec![3, 1, 7];
+
+        let result: Vec<usize> = [-2, 0, 2]
+            .iter()
+            .map(|&height_change| rules.cost(height_change))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(MovementRules::default().cost(5), 1);
+    }
+}
@@ -0,0 +1,334 @@
+use std::cmp::Ordering;
+
+use super::distress::{Packet, PacketOrder};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ComparisonStep {
+    Values {
+        path: Vec<usize>,
+        left: usize,
+        right: usize,
+    },
+    PromotedLeft {
+        path: Vec<usize>,
+        value: usize,
+    },
+    PromotedRight {
+        path: Vec<usize>,
+        value: usize,
+    },
+    ListLengths {
+        path: Vec<usize>,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl ComparisonStep {
+    pub fn path(&self) -> &[usize] {
+        match self {
+            ComparisonStep::Values { path, .. } => path,
+            ComparisonStep::PromotedLeft { path, .. } => path,
+            ComparisonStep::PromotedRight { path, .. } => path,
+            ComparisonStep::ListLengths { path, .. } => path,
+        }
+    }
+
+    // Where to point in each packet: for lists of different lengths, the first item one of them lacks.
+    fn focus(&self) -> Vec<usize> {
+        match self {
+            ComparisonStep::ListLengths { path, left, right } => {
+                let mut result = path.clone();
+
+                result.push(*left.min(right));
+
+                result
+            }
+            _ => self.path().to_vec(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        let location = path_to_string(self.path());
+
+        match self {
+            ComparisonStep::Values { left, right, .. } => {
+                format!("{}: compare {} with {}", location, left, right)
+            }
+            ComparisonStep::PromotedLeft { value, .. } => {
+                format!("{}: left value {} becomes [{}]", location, value, value)
+            }
+            ComparisonStep::PromotedRight { value, .. } => {
+                format!("{}: right value {} becomes [{}]", location, value, value)
+            }
+            ComparisonStep::ListLengths { left, right, .. } => {
+                format!("{}: left has {} items, right has {}", location, left, right)
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PacketComparison {
+    pub order: PacketOrder,
+    pub steps: Vec<ComparisonStep>,
+}
+
+impl PacketComparison {
+    // The step that settled the order, if the packets weren't equal.
+    pub fn deciding_step(&self) -> Option<&ComparisonStep> {
+        match self.order {
+            PacketOrder::Unknown => None,
+            _ => self.steps.last(),
+        }
+    }
+}
+
+fn path_to_string(path: &[usize]) -> String {
+    path.iter().fold(String::from("root"), |acc, index| {
+        format!("{}[{}]", acc, index)
+    })
+}
+
+impl Packet {
+    pub fn explain_order(&self, other: &Packet) -> PacketComparison {
+        let mut steps = vec![];
+
+        let order = match self.trace(other, &mut vec![], &mut steps) {
+            Ordering::Less => PacketOrder::In,
+            Ordering::Equal => PacketOrder::Unknown,
+            Ordering::Greater => PacketOrder::Out,
+        };
+
+        PacketComparison { order, steps }
+    }
+
+    /*
+    Shows both packets with a marker under the part that decided the order, followed
+    by every comparison made on the way there:
+
+        in order
+        left:  [[1],[2,3,4]]
+                     ^
+        right: [[1],4]
+                    ^
+        root[0][0]: compare 1 with 1
+        ...
+    */
+    pub fn diff(&self, other: &Packet) -> String {
+        let comparison = self.explain_order(other);
+
+        let summary = match comparison.order {
+            PacketOrder::In => "in order",
+            PacketOrder::Out => "out of order",
+            PacketOrder::Unknown => "equal",
+        };
+
+        let mut lines = vec![String::from(summary)];
+
+        for (label, packet) in [("left:  ", self), ("right: ", other)] {
+            lines.push(format!("{}{}", label, packet));
+
+            if let Some(step) = comparison.deciding_step() {
+                lines.push(format!(
+                    "{}^",
+                    " ".repeat(label.len() + packet.offset_at(&step.focus()))
+                ));
+            }
+        }
+
+        lines.extend(comparison.steps.iter().map(|step| step.describe()));
+
+        lines.join("\n")
+    }
+
+    fn trace(
+        &self,
+        other: &Packet,
+        path: &mut Vec<usize>,
+        steps: &mut Vec<ComparisonStep>,
+    ) -> Ordering {
+        match (self, other) {
+            (Packet::Value(l), Packet::Value(r)) => {
+                steps.push(ComparisonStep::Values {
+                    path: path.clone(),
+                    left: *l,
+                    right: *r,
+                });
+
+                l.cmp(r)
+            }
+            (Packet::List(_), Packet::Value(r)) => {
+                steps.push(ComparisonStep::PromotedRight {
+                    path: path.clone(),
+                    value: *r,
+                });
+
+                self.trace(
+                    &Packet::List(vec![Box::new(Packet::Value(*r))]),
+                    path,
+                    steps,
+                )
+            }
+            (Packet::Value(l), Packet::List(_)) => {
+                steps.push(ComparisonStep::PromotedLeft {
+                    path: path.clone(),
+                    value: *l,
+                });
+
+                Packet::List(vec![Box::new(Packet::Value(*l))]).trace(other, path, steps)
+            }
+            (Packet::List(l), Packet::List(r)) => {
+                for (index, (left, right)) in l.iter().zip(r.iter()).enumerate() {
+                    path.push(index);
+
+                    let order = left.trace(right, path, steps);
+
+                    path.pop();
+
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+
+                steps.push(ComparisonStep::ListLengths {
+                    path: path.clone(),
+                    left: l.len(),
+                    right: r.len(),
+                });
+
+                l.len().cmp(&r.len())
+            }
+        }
+    }
+
+    /*
+    The character offset of the sub-packet at `path` in this packet's `Display` output.
+    A path that runs into a value stops at that value, since it was promoted to a list
+    during the comparison, and an index past the end of a list points at its `]`.
+    */
+    fn offset_at(&self, path: &[usize]) -> usize {
+        match (self, path.split_first()) {
+            (Packet::List(sub_packets), Some((&index, rest))) => match sub_packets.get(index) {
+                Some(sub_packet) => {
+                    let preceding: usize = sub_packets[..index]
+                        .iter()
+                        .map(|packet| packet.to_string().len() + 1)
+                        .sum();
+
+                    1 + preceding + sub_packet.offset_at(rest)
+                }
+                None => self.to_string().len() - 1,
+            },
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Packet {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_explain_order_promotion() {
+        let packet = parse("[[1],[2,3,4]]");
+        let other = parse("[[1],4]");
+
+        let expected = PacketComparison {
+            order: PacketOrder::In,
+            steps: vec![
+                ComparisonStep::Values {
+                    path: vec![0, 0],
+                    left: 1,
+                    right: 1,
+                },
+                ComparisonStep::ListLengths {
+                    path: vec![0],
+                    left: 1,
+                    right: 1,
+                },
+                ComparisonStep::PromotedRight {
+                    path: vec![1],
+                    value: 4,
+                },
+                ComparisonStep::Values {
+                    path: vec![1, 0],
+                    left: 2,
+                    right: 4,
+                },
+            ],
+        };
+
+        let result = packet.explain_order(&other);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_explain_order_agrees_with_get_order() {
+        let input = [
+            ("[1,1,3,1,1]", "[1,1,5,1,1]"),
+            ("[9]", "[[8,7,6]]"),
+            ("[[4,4],4,4]", "[[4,4],4,4,4]"),
+            ("[7,7,7,7]", "[7,7,7]"),
+            ("[[[]]]", "[[]]"),
+            ("[1,[2]]", "[1,2]"),
+        ];
+
+        let expected: Vec<PacketOrder> = input
+            .iter()
+            .map(|(packet, other)| parse(packet).get_order(&parse(other)))
+            .collect();
+
+        let result: Vec<PacketOrder> = input
+            .iter()
+            .map(|(packet, other)| parse(packet).explain_order(&parse(other)).order)
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_diff() {
+        let packet = parse("[1,[2,[3,[4,[5,6,7]]]],8,9]");
+        let other = parse("[1,[2,[3,[4,[5,6,0]]]],8,9]");
+
+        let result = packet.diff(&other);
+
+        let lines: Vec<&str> = result.lines().collect();
+
+        assert_eq!(lines[0], "out of order");
+        assert_eq!(lines[1], "left:  [1,[2,[3,[4,[5,6,7]]]],8,9]");
+        assert_eq!(lines[2], format!("{}^", " ".repeat(24)));
+        assert_eq!(lines[3], "right: [1,[2,[3,[4,[5,6,0]]]],8,9]");
+        assert_eq!(lines[4], format!("{}^", " ".repeat(24)));
+        assert_eq!(lines.last(), Some(&"root[1][1][1][1][2]: compare 7 with 0"));
+    }
+
+    #[test]
+    fn test_diff_list_runs_out() {
+        let packet = parse("[7,7,7,7]");
+        let other = parse("[7,7,7]");
+
+        let expected = [
+            "out of order",
+            "left:  [7,7,7,7]",
+            "              ^",
+            "right: [7,7,7]",
+            "             ^",
+            "root[0]: compare 7 with 7",
+            "root[1]: compare 7 with 7",
+            "root[2]: compare 7 with 7",
+            "root: left has 4 items, right has 3",
+        ]
+        .join("\n");
+
+        let result = packet.diff(&other);
+
+        assert_eq!(result, expected);
+    }
+}
@@ -1,5 +1,10 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
 use std::vec;
 
+#[cfg(test)]
 #[derive(Debug, PartialEq)]
 pub enum PacketOrder {
     Unknown,
@@ -7,177 +12,355 @@ pub enum PacketOrder {
     Out,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Packet {
     Value(usize),
     List(Vec<Box<Packet>>),
 }
 
 impl Packet {
+    #[cfg(test)]
     pub fn get_order(&self, other: &Packet) -> PacketOrder {
-        match (&self, &other) {
-            (&Packet::Value(l), &Packet::Value(r)) if l < r => PacketOrder::In,
-            (&Packet::Value(l), &Packet::Value(r)) if l == r => PacketOrder::Unknown,
-            (&Packet::Value(l), &Packet::Value(r)) if r < l => PacketOrder::Out,
-            (&Packet::List(_), &Packet::Value(r)) => {
-                self.get_order(&Packet::List(vec![Box::new(Packet::Value(*r))]))
+        match self.compare(other) {
+            Ordering::Less => PacketOrder::In,
+            Ordering::Equal => PacketOrder::Unknown,
+            Ordering::Greater => PacketOrder::Out,
+        }
+    }
+
+    // The ordering from the puzzle rules, where a value and a list holding only that value are equal.
+    pub fn compare(&self, other: &Packet) -> Ordering {
+        match (self, other) {
+            (Packet::Value(l), Packet::Value(r)) => l.cmp(r),
+            (Packet::List(_), Packet::Value(r)) => {
+                self.compare(&Packet::List(vec![Box::new(Packet::Value(*r))]))
             }
-            (&Packet::Value(l), &Packet::List(_)) => {
-                Packet::List(vec![Box::new(Packet::Value(*l))]).get_order(other)
+            (Packet::Value(l), Packet::List(_)) => {
+                Packet::List(vec![Box::new(Packet::Value(*l))]).compare(other)
             }
-            (&Packet::List(l), &Packet::List(r)) => {
-                let mut result = PacketOrder::Unknown;
-
-                for (left, right) in l.iter().zip(r.iter()) {
-                    match left.get_order(right) {
-                        PacketOrder::Unknown => continue,
-                        PacketOrder::In => {
-                            result = PacketOrder::In;
-
-                            break;
-                        }
-                        PacketOrder::Out => {
-                            result = PacketOrder::Out;
-
-                            break;
-                        }
-                    }
-                }
+            (Packet::List(l), Packet::List(r)) => l
+                .iter()
+                .zip(r.iter())
+                .map(|(left, right)| left.compare(right))
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or_else(|| l.len().cmp(&r.len())),
+        }
+    }
 
-                match result {
-                    PacketOrder::Unknown if l.len() < r.len() => PacketOrder::In,
-                    PacketOrder::Unknown if r.len() < l.len() => PacketOrder::Out,
-                    _ => result,
-                }
-            }
-            _ => panic!(),
+    // Breaks the ties left by the puzzle rules, so that only identical packets are equal.
+    fn compare_structure(&self, other: &Packet) -> Ordering {
+        match (self, other) {
+            (Packet::Value(l), Packet::Value(r)) => l.cmp(r),
+            (Packet::Value(_), Packet::List(_)) => Ordering::Less,
+            (Packet::List(_), Packet::Value(_)) => Ordering::Greater,
+            (Packet::List(l), Packet::List(r)) => l
+                .iter()
+                .zip(r.iter())
+                .map(|(left, right)| left.compare_structure(right))
+                .find(|order| *order != Ordering::Equal)
+                .unwrap_or_else(|| l.len().cmp(&r.len())),
         }
     }
 
-    fn get_size(&self) -> usize {
-        match &self {
-            Packet::Value(mut x) => {
-                let mut result = 1;
+    #[cfg(test)]
+    pub fn to_json(&self) -> String {
+        self.to_string()
+    }
 
-                while x >= 10 {
-                    x /= 10;
+    /*
+    Packets are a subset of JSON: arrays and non-negative integers. A JSON document is
+    only a packet if its top level is an array, which is what every line of the
+    distress signal holds.
+    */
+    #[cfg(test)]
+    pub fn from_json(input: &str) -> Result<Packet, String> {
+        match input.parse()? {
+            Packet::Value(_) => Err(String::from("A packet must be a JSON array")),
+            packet => Ok(packet),
+        }
+    }
+}
 
-                    result += 1;
-                }
+/*
+Packets that the puzzle rules can't tell apart, like `[1]` and `1`, are ordered by
+their structure so that `Ord` agrees with `Eq`. Any two packets the puzzle rules do
+order keep that order.
+*/
+impl Ord for Packet {
+    fn cmp(&self, other: &Packet) -> Ordering {
+        self.compare(other)
+            .then_with(|| self.compare_structure(other))
+    }
+}
 
-                result
-            }
-            Packet::List(l) => {
-                let number_of_commas = l.len().saturating_sub(1);
-                let number_of_brackets = 2;
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Packet) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-                let size_of_subpackets: usize = l.iter().map(|packet| packet.get_size()).sum();
+pub fn sort_packets(packets: &mut [Packet]) {
+    packets.sort();
+}
 
-                number_of_commas + number_of_brackets + size_of_subpackets
-            }
+pub fn insertion_index<P: Borrow<Packet>>(sorted_packets: &[P], packet: &Packet) -> usize {
+    sorted_packets.partition_point(|sorted_packet| sorted_packet.borrow() < packet)
+}
+
+// Keeps one packet out of every group the puzzle rules consider equal.
+#[cfg(test)]
+pub fn dedup_packets(packets: &mut Vec<Packet>) {
+    packets.sort();
+    packets.dedup_by(|packet, other| packet.compare(other) == Ordering::Equal);
+}
+
+struct PacketParser<'a> {
+    input: &'a [char],
+    position: usize,
+}
+
+impl<'a> PacketParser<'a> {
+    fn new(input: &'a [char]) -> PacketParser<'a> {
+        PacketParser { input, position: 0 }
+    }
+
+    fn is_finished(&mut self) -> bool {
+        self.skip_whitespace();
+
+        self.position == self.input.len()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.position += 1;
         }
     }
-}
 
-impl From<&[char]> for Packet {
-    fn from(input: &[char]) -> Packet {
-        let mut current_index = 0;
-        let mut current_value = 0;
-        let mut sub_packets: Vec<Box<Packet>> = vec![];
+    fn peek(&self) -> Option<char> {
+        self.input.get(self.position).cloned()
+    }
 
-        let mut current_packet: Option<Packet> = None;
+    fn unexpected(&self, expected: &str) -> String {
+        match self.peek() {
+            Some(c) => format!(
+                "Expected {} at position {}, found '{}'",
+                expected, self.position, c
+            ),
+            None => format!(
+                "Expected {} at position {}, found the end of the input",
+                expected, self.position
+            ),
+        }
+    }
 
-        while current_index < input.len() {
-            let current_char = input[current_index];
+    fn parse_packet(&mut self) -> Result<Packet, String> {
+        self.skip_whitespace();
 
-            match current_char {
-                ('0'..='9') => {
-                    current_packet = Some(Packet::Value(0));
+        match self.peek() {
+            Some('[') => self.parse_list(),
+            Some('0'..='9') => self.parse_value(),
+            _ => Err(self.unexpected("'[' or a digit")),
+        }
+    }
 
-                    current_value *= 10;
-                    current_value += current_char.to_digit(10).unwrap();
-                }
-                '[' => {
-                    current_packet = Some(Packet::List(vec![]));
+    fn parse_list(&mut self) -> Result<Packet, String> {
+        let mut sub_packets = vec![];
 
-                    if input[current_index + 1] == ']' {
-                        break;
-                    }
+        self.position += 1;
+        self.skip_whitespace();
 
-                    let temp = Box::new(Packet::from(&input[(current_index + 1)..]));
+        if self.peek() == Some(']') {
+            self.position += 1;
 
-                    current_index += temp.get_size();
+            return Ok(Packet::List(sub_packets));
+        }
 
-                    sub_packets.push(temp);
-                }
-                ',' => match current_packet {
-                    Some(Packet::Value(_)) => break,
-                    Some(Packet::List(_)) => {
-                        let temp = Box::new(Packet::from(&input[(current_index + 1)..]));
+        loop {
+            sub_packets.push(Box::new(self.parse_packet()?));
 
-                        current_index += temp.get_size();
+            self.skip_whitespace();
 
-                        sub_packets.push(temp);
-                    }
-                    None => panic!(),
-                },
-                ']' => {
-                    break;
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+
+                    return Ok(Packet::List(sub_packets));
                 }
-                _ => panic!(),
+                _ => return Err(self.unexpected("',' or ']'")),
             }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Packet, String> {
+        let start = self.position;
+        let mut value: usize = 0;
+
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(digit as usize))
+                .ok_or_else(|| format!("Value at position {} is too large", start))?;
+
+            self.position += 1;
+        }
+
+        Ok(Packet::Value(value))
+    }
+}
+
+impl FromStr for Packet {
+    type Err = String;
 
-            current_index += 1;
+    fn from_str(input: &str) -> Result<Packet, String> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut parser = PacketParser::new(&chars);
+
+        let packet = parser.parse_packet()?;
+
+        if !parser.is_finished() {
+            return Err(parser.unexpected("the end of the packet"));
         }
 
-        match current_packet {
-            Some(Packet::Value(_)) => Packet::Value(current_value as usize),
-            Some(Packet::List(_)) => Packet::List(sub_packets),
-            None => panic!(),
+        Ok(packet)
+    }
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Packet::Value(value) => write!(f, "{}", value),
+            Packet::List(sub_packets) => {
+                write!(f, "[")?;
+
+                for (index, packet) in sub_packets.iter().enumerate() {
+                    if index != 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", packet)?;
+                }
+
+                write!(f, "]")
+            }
         }
     }
 }
 
+// Parses the first packet in the input and ignores anything after it.
+impl From<&[char]> for Packet {
+    fn from(input: &[char]) -> Packet {
+        PacketParser::new(input)
+            .parse_packet()
+            .unwrap_or_else(|error| panic!("Invalid packet: {}", error))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::collections::BTreeSet;
+
+    use crate::day_13::packet_generator::{PacketGenerator, PacketGeneratorConfig};
+    use crate::util::file_reader::to_string_vector;
+
     #[test]
-    fn test_packet_value_size() {
-        let packet_1 = Packet::Value(1);
-        let packet_2 = Packet::Value(10);
-        let packet_3 = Packet::Value(110);
-        let packet_4 = Packet::Value(0);
+    fn test_packet_display() {
+        let packet = Packet::List(vec![
+            Box::new(Packet::List(vec![
+                Box::new(Packet::Value(1)),
+                Box::new(Packet::Value(110)),
+            ])),
+            Box::new(Packet::Value(30)),
+            Box::new(Packet::List(vec![])),
+        ]);
 
-        let expected_1 = 1;
-        let expected_2 = 2;
-        let expected_3 = 3;
-        let expected_4 = 1;
+        let expected = "[[1,110],30,[]]";
 
-        let result_1 = packet_1.get_size();
-        let result_2 = packet_2.get_size();
-        let result_3 = packet_3.get_size();
-        let result_4 = packet_4.get_size();
+        let result = packet.to_string();
 
-        assert_eq!(result_1, expected_1);
-        assert_eq!(result_2, expected_2);
-        assert_eq!(result_3, expected_3);
-        assert_eq!(result_4, expected_4);
+        assert_eq!(result, expected);
     }
 
     #[test]
-    fn test_packet_list_of_values_size() {
-        let packet = Packet::List(vec![
-            Box::new(Packet::Value(1)),
-            Box::new(Packet::Value(110)),
-            Box::new(Packet::Value(30)),
-            Box::new(Packet::Value(1)),
-            Box::new(Packet::Value(1)),
-        ]);
+    fn test_packet_from_str_round_trip() {
+        let input = [
+            "[1,1,3,1,1]",
+            "[[1],[2,3,4]]",
+            "[[[]]]",
+            "[1,[2,[3,[4,[5,6,0]]]],8,9]",
+            "[]",
+            "1234",
+        ];
+
+        let expected: Vec<String> = input.iter().map(|line| line.to_string()).collect();
+
+        let result: Vec<String> = input
+            .iter()
+            .map(|line| line.parse::<Packet>().unwrap().to_string())
+            .collect();
+
+        assert_eq!(result, expected);
+    }
 
-        let expected = 14;
+    #[test]
+    fn test_packet_from_str_whitespace() {
+        let expected = "[[1,10],[]]".parse::<Packet>();
 
-        let result = packet.get_size();
+        let result = " [ [1, 10],\n [ ] ] ".parse::<Packet>();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_packet_from_str_errors() {
+        let input = [
+            "[1,2",
+            "[1;2]",
+            "[1,2]]",
+            "[1,,2]",
+            "",
+            "[99999999999999999999999]",
+        ];
+
+        let expected = vec![
+            Err(String::from(
+                "Expected ',' or ']' at position 4, found the end of the input",
+            )),
+            Err(String::from("Expected ',' or ']' at position 2, found ';'")),
+            Err(String::from(
+                "Expected the end of the packet at position 5, found ']'",
+            )),
+            Err(String::from(
+                "Expected '[' or a digit at position 3, found ','",
+            )),
+            Err(String::from(
+                "Expected '[' or a digit at position 0, found the end of the input",
+            )),
+            Err(String::from("Value at position 1 is too large")),
+        ];
+
+        let result: Vec<Result<Packet, String>> =
+            input.iter().map(|line| line.parse::<Packet>()).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_packet_json() {
+        let packet: Packet = "[[4,4],4,4]".parse().unwrap();
+
+        let result = Packet::from_json(&packet.to_json());
+
+        assert_eq!(result, Ok(packet));
+    }
+
+    #[test]
+    fn test_packet_from_json_not_an_array() {
+        let expected = Err(String::from("A packet must be a JSON array"));
+
+        let result = Packet::from_json("42");
 
         assert_eq!(result, expected);
     }
@@ -252,6 +435,159 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    fn parse_all(input: &[&str]) -> Vec<Packet> {
+        input.iter().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_packet_ord_agrees_with_get_order() {
+        let input = to_string_vector("test_inputs/day_13_part_1.txt").unwrap();
+
+        let pairs: Vec<(Packet, Packet)> = input
+            .split(|line| line.is_empty())
+            .map(|pair| (pair[0].parse().unwrap(), pair[1].parse().unwrap()))
+            .collect();
+
+        let expected: Vec<bool> = pairs
+            .iter()
+            .map(|(first, second)| first.get_order(second) == PacketOrder::In)
+            .collect();
+
+        let result: Vec<bool> = pairs.iter().map(|(first, second)| first < second).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_packet_ord_breaks_ties_by_structure() {
+        let packets = parse_all(&["[[1]]", "1", "[1]"]);
+
+        let expected = parse_all(&["1", "[1]", "[[1]]"]);
+
+        let mut result = packets.clone();
+        sort_packets(&mut result);
+
+        assert_eq!(result, expected);
+        assert_eq!(packets[0].compare(&packets[1]), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_sort_packets() {
+        let mut result = parse_all(&["[[1],4]", "[]", "[1,1,5,1,1]", "[[]]", "[1,1,3,1,1]", "[3]"]);
+
+        let expected = parse_all(&["[]", "[[]]", "[1,1,3,1,1]", "[1,1,5,1,1]", "[[1],4]", "[3]"]);
+
+        sort_packets(&mut result);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_insertion_index() {
+        let sorted_packets = parse_all(&["[]", "[[]]", "[1,1,3,1,1]", "[[1],4]", "[3]"]);
+
+        let expected = vec![0, 2, 4, 5];
+
+        let result: Vec<usize> = parse_all(&["[]", "[[1]]", "[[2]]", "[[6]]"])
+            .iter()
+            .map(|divider| insertion_index(&sorted_packets, divider))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_dedup_packets() {
+        let mut result = parse_all(&["[2]", "[[1]]", "1", "[]", "[2]", "[1]"]);
+
+        let expected = parse_all(&["[]", "1", "[2]"]);
+
+        dedup_packets(&mut result);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_packets_in_btree_set() {
+        let result: BTreeSet<Packet> = parse_all(&["[3]", "[1]", "[3]", "[2]"])
+            .into_iter()
+            .collect();
+
+        let expected = parse_all(&["[1]", "[2]", "[3]"]);
+
+        assert_eq!(result.into_iter().collect::<Vec<Packet>>(), expected);
+    }
+
+    fn generated_packets(seed: u64, count: usize) -> Vec<Packet> {
+        let config = PacketGeneratorConfig {
+            max_depth: 5,
+            max_breadth: 4,
+            max_value: 120,
+        };
+
+        PacketGenerator::new(config, seed).take(count).collect()
+    }
+
+    #[test]
+    fn test_packet_display_round_trip_property() {
+        let packets = generated_packets(1, 500);
+
+        let result: Vec<Packet> = packets
+            .iter()
+            .map(|packet| packet.to_string().parse().unwrap())
+            .collect();
+
+        assert_eq!(result, packets);
+    }
+
+    #[test]
+    fn test_packet_get_order_antisymmetric_property() {
+        let packets = generated_packets(2, 200);
+
+        for packet in packets.iter() {
+            for other in packets.iter() {
+                let expected = match other.get_order(packet) {
+                    PacketOrder::In => PacketOrder::Out,
+                    PacketOrder::Out => PacketOrder::In,
+                    PacketOrder::Unknown => PacketOrder::Unknown,
+                };
+
+                let result = packet.get_order(other);
+
+                assert_eq!(result, expected, "{} vs {}", packet, other);
+            }
+        }
+    }
+
+    #[test]
+    fn test_packet_get_order_transitive_property() {
+        // Narrow packets with small values tie and nest often, which is where bugs hide.
+        let config = PacketGeneratorConfig {
+            max_depth: 3,
+            max_breadth: 2,
+            max_value: 2,
+        };
+
+        let packets: Vec<Packet> = PacketGenerator::new(config, 3).take(60).collect();
+
+        let not_after =
+            |packet: &Packet, other: &Packet| packet.get_order(other) != PacketOrder::Out;
+
+        for first in packets.iter() {
+            for second in packets.iter().filter(|second| not_after(first, second)) {
+                for third in packets.iter().filter(|third| not_after(second, third)) {
+                    assert!(
+                        not_after(first, third),
+                        "{} <= {} <= {}",
+                        first,
+                        second,
+                        third
+                    );
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_packet_unknown_order_just_values() {
         let packet = Packet::Value(1);
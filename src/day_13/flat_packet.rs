@@ -0,0 +1,340 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::distress::Packet;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Token {
+    Open,
+    Close,
+    Value(usize),
+}
+
+/*
+Every packet is stored as its tokens, back to back in one buffer, so a packet is just
+a range of that buffer and adding one costs no allocation beyond the buffer growing.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PacketArena {
+    tokens: Vec<Token>,
+    offsets: Vec<usize>,
+}
+
+impl PacketArena {
+    pub fn new() -> PacketArena {
+        PacketArena::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn push(&mut self, packet: &Packet) -> usize {
+        self.offsets.push(self.tokens.len());
+
+        push_tokens(packet, &mut self.tokens);
+
+        self.offsets.len() - 1
+    }
+
+    pub fn push_str(&mut self, input: &str) -> Result<usize, String> {
+        Ok(self.push(&input.parse()?))
+    }
+
+    pub fn get(&self, index: usize) -> Option<FlatPacket<'_>> {
+        let start = *self.offsets.get(index)?;
+        let end = self
+            .offsets
+            .get(index + 1)
+            .cloned()
+            .unwrap_or(self.tokens.len());
+
+        Some(FlatPacket {
+            tokens: &self.tokens[start..end],
+        })
+    }
+
+    pub fn compare(&self, index: usize, other: usize) -> Option<Ordering> {
+        Some(self.get(index)?.compare(&self.get(other)?))
+    }
+}
+
+fn push_tokens(packet: &Packet, tokens: &mut Vec<Token>) {
+    match packet {
+        Packet::Value(value) => tokens.push(Token::Value(*value)),
+        Packet::List(sub_packets) => {
+            tokens.push(Token::Open);
+
+            sub_packets
+                .iter()
+                .for_each(|sub_packet| push_tokens(sub_packet, tokens));
+
+            tokens.push(Token::Close);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FlatPacket<'a> {
+    tokens: &'a [Token],
+}
+
+impl<'a> FlatPacket<'a> {
+    #[cfg(test)]
+    pub fn tokens(&self) -> &'a [Token] {
+        self.tokens
+    }
+
+    // Follows the same rules as `Packet::compare`, without building any lists.
+    pub fn compare(&self, other: &FlatPacket) -> Ordering {
+        let mut left = TokenCursor::new(self.tokens);
+        let mut right = TokenCursor::new(other.tokens);
+
+        loop {
+            match (left.peek(), right.peek()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(Token::Value(l)), Some(Token::Value(r))) if l != r => return l.cmp(&r),
+                (Some(Token::Close), Some(Token::Close))
+                | (Some(Token::Open), Some(Token::Open))
+                | (Some(Token::Value(_)), Some(Token::Value(_))) => {
+                    left.advance();
+                    right.advance();
+                }
+                (Some(Token::Close), Some(_)) => return Ordering::Less,
+                (Some(_), Some(Token::Close)) => return Ordering::Greater,
+                (Some(Token::Value(_)), Some(Token::Open)) => {
+                    left.wrap();
+                    right.advance();
+                }
+                (Some(Token::Open), Some(Token::Value(_))) => {
+                    left.advance();
+                    right.wrap();
+                }
+            }
+        }
+    }
+
+    pub fn to_packet(self) -> Packet {
+        let mut stack: Vec<Vec<Box<Packet>>> = vec![vec![]];
+
+        for token in self.tokens {
+            match token {
+                Token::Open => stack.push(vec![]),
+                Token::Close => {
+                    let list = Packet::List(stack.pop().expect("Tokens are balanced"));
+
+                    stack
+                        .last_mut()
+                        .expect("Tokens are balanced")
+                        .push(Box::new(list));
+                }
+                Token::Value(value) => stack
+                    .last_mut()
+                    .expect("Tokens are balanced")
+                    .push(Box::new(Packet::Value(*value))),
+            }
+        }
+
+        *stack
+            .pop()
+            .and_then(|mut packets| packets.pop())
+            .expect("A flat packet holds exactly one packet")
+    }
+}
+
+impl fmt::Display for FlatPacket<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, token) in self.tokens.iter().enumerate() {
+            let follows_item = index != 0 && self.tokens[index - 1] != Token::Open;
+
+            match token {
+                Token::Open | Token::Value(_) if follows_item => write!(f, ",")?,
+                _ => (),
+            }
+
+            match token {
+                Token::Open => write!(f, "[")?,
+                Token::Close => write!(f, "]")?,
+                Token::Value(value) => write!(f, "{}", value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/*
+Comparing a value with a list treats the value as a list holding only that value.
+Instead of building that list, the cursor remembers how many lists the value is
+wrapped in and hands out the matching closing tokens once the value is consumed.
+*/
+struct TokenCursor<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    wrapped: usize,
+    pending_closes: usize,
+}
+
+impl<'a> TokenCursor<'a> {
+    fn new(tokens: &'a [Token]) -> TokenCursor<'a> {
+        TokenCursor {
+            tokens,
+            position: 0,
+            wrapped: 0,
+            pending_closes: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<Token> {
+        if self.pending_closes != 0 {
+            Some(Token::Close)
+        } else {
+            self.tokens.get(self.position).cloned()
+        }
+    }
+
+    fn advance(&mut self) {
+        if self.pending_closes != 0 {
+            self.pending_closes -= 1;
+
+            return;
+        }
+
+        if let Some(Token::Value(_)) = self.tokens.get(self.position) {
+            self.pending_closes = self.wrapped;
+            self.wrapped = 0;
+        }
+
+        self.position += 1;
+    }
+
+    fn wrap(&mut self) {
+        self.wrapped += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::day_13::packet_generator::{PacketGenerator, PacketGeneratorConfig};
+    use crate::util::file_reader::to_string_vector;
+
+    #[test]
+    fn test_push_and_get() {
+        let mut arena = PacketArena::new();
+
+        arena.push_str("[1,[2]]").unwrap();
+        arena.push_str("[]").unwrap();
+
+        let expected = vec![
+            Token::Open,
+            Token::Value(1),
+            Token::Open,
+            Token::Value(2),
+            Token::Close,
+            Token::Close,
+        ];
+
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena.get(0).unwrap().tokens(), expected.as_slice());
+        assert_eq!(
+            arena.get(1).unwrap().tokens(),
+            [Token::Open, Token::Close].as_slice()
+        );
+        assert_eq!(arena.get(2), None);
+    }
+
+    #[test]
+    fn test_to_packet_and_display_round_trip() {
+        let input = [
+            "[1,[2,[3,[4,[5,6,0]]]],8,9]",
+            "[[[]]]",
+            "[]",
+            "[[],[1],10]",
+            "7",
+        ];
+
+        let mut arena = PacketArena::new();
+
+        input.iter().for_each(|line| {
+            arena.push_str(line).unwrap();
+        });
+
+        let expected: Vec<Packet> = input.iter().map(|line| line.parse().unwrap()).collect();
+
+        let result: Vec<Packet> = (0..arena.len())
+            .map(|index| arena.get(index).unwrap().to_packet())
+            .collect();
+
+        let displayed: Vec<String> = (0..arena.len())
+            .map(|index| arena.get(index).unwrap().to_string())
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(displayed, input);
+    }
+
+    #[test]
+    fn test_compare_agrees_with_packet_compare() {
+        let mut input = to_string_vector("test_inputs/day_13_part_1.txt").unwrap();
+        input.retain(|line| !line.is_empty());
+        input.extend(
+            [
+                "1", "[1]", "[[1]]", "[[1],2]", "[1,[2]]", "[[[]]]", "[[]]", "5", "[[5,[]]]",
+            ]
+            .map(String::from),
+        );
+
+        let packets: Vec<Packet> = input.iter().map(|line| line.parse().unwrap()).collect();
+
+        let mut arena = PacketArena::new();
+
+        packets.iter().for_each(|packet| {
+            arena.push(packet);
+        });
+
+        for (index, packet) in packets.iter().enumerate() {
+            for (other_index, other) in packets.iter().enumerate() {
+                let expected = Some(packet.compare(other));
+
+                let result = arena.compare(index, other_index);
+
+                assert_eq!(result, expected, "{} vs {}", packet, other);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_agrees_with_packet_compare_property() {
+        let config = PacketGeneratorConfig {
+            max_depth: 6,
+            max_breadth: 3,
+            max_value: 4,
+        };
+
+        let packets: Vec<Packet> = PacketGenerator::new(config, 37).take(300).collect();
+
+        let mut arena = PacketArena::new();
+
+        packets.iter().for_each(|packet| {
+            arena.push(packet);
+        });
+
+        for index in 0..packets.len() {
+            let other_index = (index * 7 + 3) % packets.len();
+
+            let expected = Some(packets[index].compare(&packets[other_index]));
+
+            let result = arena.compare(index, other_index);
+
+            assert_eq!(
+                result, expected,
+                "{} vs {}",
+                packets[index], packets[other_index]
+            );
+        }
+    }
+}
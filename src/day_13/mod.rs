@@ -1,75 +1,64 @@
+#[cfg(test)]
+mod comparison;
 mod distress;
+mod flat_packet;
+#[cfg(test)]
+mod packet_generator;
 
 use std::cmp::Ordering;
 
 use crate::util::file_reader::to_string_vector;
 
-use distress::{Packet, PacketOrder};
+use distress::{insertion_index, sort_packets, Packet};
+use flat_packet::PacketArena;
 
 pub fn run() {
     let input = to_string_vector("inputs/day_13.txt").expect("Something went wrong Day 13!");
 
-    let packets = get_packet_pairs(&input);
+    let packets = get_packet_arena(&input);
 
     println!("Day 13, Part 1: {}", part_1(&packets));
     println!("Day 13, Part 2: {}", part_2(&packets));
 }
 
-fn part_1(packets: &[(Packet, Packet)]) -> usize {
-    packets
-        .iter()
-        .enumerate()
-        .map(|(index, (first, second))| (index + 1, first.get_order(second)))
-        .filter(|(_, order)| *order == PacketOrder::In)
-        .fold(0, |acc, (index, _)| acc + index)
+// Pairs are stored back to back, so pair `n` is made of packets `2n` and `2n + 1`.
+fn part_1(packets: &PacketArena) -> usize {
+    (0..packets.len() / 2)
+        .filter(|pair| packets.compare(2 * pair, 2 * pair + 1) == Some(Ordering::Less))
+        .fold(0, |acc, pair| acc + pair + 1)
 }
 
-fn part_2(packets: &[(Packet, Packet)]) -> usize {
-    let divider_packet_1 = Packet::from(vec!['[', '[', '6', ']', ']'].as_slice());
-    let divider_packet_2 = Packet::from(vec!['[', '[', '2', ']', ']'].as_slice());
-
-    let mut result: Vec<&Packet> = packets
-        .iter()
-        .map(|(first, other)| match first.get_order(&other) {
-            PacketOrder::In => vec![first, other],
-            PacketOrder::Out => vec![other, first],
-            PacketOrder::Unknown => panic!(),
-        })
-        .flatten()
+fn part_2(packets: &PacketArena) -> usize {
+    let divider_packet_1: Packet = "[[2]]".parse().unwrap();
+    let divider_packet_2: Packet = "[[6]]".parse().unwrap();
+
+    let mut sorted_packets: Vec<Packet> = (0..packets.len())
+        .filter_map(|index| packets.get(index))
+        .map(|packet| packet.to_packet())
         .collect();
 
-    result.push(&divider_packet_1);
-    result.push(&divider_packet_2);
-
-    result.sort_by(|packet, other| match packet.get_order(other) {
-        PacketOrder::In => Ordering::Less,
-        PacketOrder::Out => Ordering::Greater,
-        PacketOrder::Unknown => Ordering::Equal,
-    });
-
-    result
-        .iter()
-        .enumerate()
-        .filter(|(_, &packet)| packet == &divider_packet_1 || packet == &divider_packet_2)
-        .map(|(index, _)| index + 1)
-        .fold(1, |acc, index| acc * index)
-}
+    sort_packets(&mut sorted_packets);
 
-fn get_packet_pairs(input: &[String]) -> Vec<(Packet, Packet)> {
-    input
-        .split(|line| line.is_empty())
-        .map(|split| get_pair(split))
-        .collect()
+    // The first divider sorts before the second, which shifts the second one along by one.
+    let divider_index_1 = insertion_index(&sorted_packets, &divider_packet_1) + 1;
+    let divider_index_2 = insertion_index(&sorted_packets, &divider_packet_2) + 2;
+
+    divider_index_1 * divider_index_2
 }
 
-fn get_pair(input: &[String]) -> (Packet, Packet) {
-    let left_chars: Vec<char> = input[0].chars().collect();
-    let right_chars: Vec<char> = input[1].chars().collect();
+fn get_packet_arena(input: &[String]) -> PacketArena {
+    let mut packets = PacketArena::new();
 
-    let left = Packet::from(left_chars.as_slice());
-    let right = Packet::from(right_chars.as_slice());
+    input
+        .split(|line| line.is_empty())
+        .flat_map(|split| &split[..2])
+        .for_each(|line| {
+            packets
+                .push_str(line)
+                .unwrap_or_else(|error| panic!("Invalid packet {}: {}", line, error));
+        });
 
-    (left, right)
+    packets
 }
 
 #[cfg(test)]
@@ -80,7 +69,7 @@ mod tests {
     fn test_part_1() {
         let input = to_string_vector("test_inputs/day_13_part_1.txt").unwrap();
 
-        let packets = get_packet_pairs(&input);
+        let packets = get_packet_arena(&input);
 
         let expected = 13;
 
@@ -93,7 +82,7 @@ mod tests {
     fn test_part_2() {
         let input = to_string_vector("test_inputs/day_13_part_1.txt").unwrap();
 
-        let packets = get_packet_pairs(&input);
+        let packets = get_packet_arena(&input);
 
         let expected = 140;
 
@@ -0,0 +1,129 @@
+use crate::util::random::Random;
+
+use super::distress::Packet;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PacketGeneratorConfig {
+    pub max_depth: usize,
+    pub max_breadth: usize,
+    pub max_value: usize,
+}
+
+impl Default for PacketGeneratorConfig {
+    fn default() -> PacketGeneratorConfig {
+        PacketGeneratorConfig {
+            max_depth: 4,
+            max_breadth: 5,
+            max_value: 10,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct PacketGenerator {
+    config: PacketGeneratorConfig,
+    random: Random,
+}
+
+impl PacketGenerator {
+    pub fn new(config: PacketGeneratorConfig, seed: u64) -> PacketGenerator {
+        PacketGenerator {
+            config,
+            random: Random::new(seed),
+        }
+    }
+
+    // Like the puzzle input, the outermost packet is always a list.
+    pub fn next_packet(&mut self) -> Packet {
+        self.next_list(1)
+    }
+
+    fn next_list(&mut self, depth: usize) -> Packet {
+        let length = self.random.next_in_range(0, self.config.max_breadth);
+
+        Packet::List(
+            (0..length)
+                .map(|_| Box::new(self.next_sub_packet(depth)))
+                .collect(),
+        )
+    }
+
+    fn next_sub_packet(&mut self, depth: usize) -> Packet {
+        if (depth < self.config.max_depth) && self.random.next_bool() {
+            self.next_list(depth + 1)
+        } else {
+            Packet::Value(self.random.next_in_range(0, self.config.max_value))
+        }
+    }
+}
+
+impl Iterator for PacketGenerator {
+    type Item = Packet;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_packet())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(packet: &Packet) -> usize {
+        match packet {
+            Packet::Value(_) => 0,
+            Packet::List(sub_packets) => {
+                1 + sub_packets.iter().map(|p| depth(p)).max().unwrap_or(0)
+            }
+        }
+    }
+
+    fn breadth(packet: &Packet) -> usize {
+        match packet {
+            Packet::Value(_) => 0,
+            Packet::List(sub_packets) => sub_packets
+                .iter()
+                .map(|p| breadth(p))
+                .max()
+                .unwrap_or(0)
+                .max(sub_packets.len()),
+        }
+    }
+
+    fn max_value(packet: &Packet) -> usize {
+        match packet {
+            Packet::Value(value) => *value,
+            Packet::List(sub_packets) => {
+                sub_packets.iter().map(|p| max_value(p)).max().unwrap_or(0)
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_packets() {
+        let config = PacketGeneratorConfig::default();
+
+        let expected: Vec<Packet> = PacketGenerator::new(config, 13).take(20).collect();
+
+        let result: Vec<Packet> = PacketGenerator::new(config, 13).take(20).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_packets_respect_config() {
+        let config = PacketGeneratorConfig {
+            max_depth: 6,
+            max_breadth: 3,
+            max_value: 150,
+        };
+
+        let packets: Vec<Packet> = PacketGenerator::new(config, 99).take(500).collect();
+
+        assert!(packets.iter().all(|packet| depth(packet) <= 6));
+        assert!(packets.iter().all(|packet| breadth(packet) <= 3));
+        assert!(packets.iter().all(|packet| max_value(packet) <= 150));
+        assert!(packets.iter().any(|packet| depth(packet) == 6));
+        assert!(packets.iter().any(|packet| max_value(packet) >= 100));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::util::point_2d::Point2d;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Floor {
+    // Sand that falls below the lowest rock is lost.
+    Abyss,
+    // An endless floor at this depth.
+    #[cfg(test)]
+    At(i32),
+    // An endless floor this far below the lowest rock, which moves down as rocks are added.
+    BelowLowestRock(i32),
+}
+
+/*
+What surrounds the rocks. The default is the first part of the puzzle: no walls and an
+abyss below the lowest rock. Walls are solid columns at `left` and `right` and stop sand
+from spilling past them.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Boundary {
+    pub floor: Floor,
+    pub walls: Option<(i32, i32)>,
+}
+
+impl Default for Boundary {
+    fn default() -> Boundary {
+        Boundary {
+            floor: Floor::Abyss,
+            walls: None,
+        }
+    }
+}
+
+impl Boundary {
+    pub fn floor_depth(&self, lowest_rock: i32) -> Option<i32> {
+        match self.floor {
+            Floor::Abyss => None,
+            #[cfg(test)]
+            Floor::At(depth) => Some(depth),
+            Floor::BelowLowestRock(offset) => Some(lowest_rock + offset),
+        }
+    }
+
+    pub fn blocks(&self, point: &Point2d<i32>, lowest_rock: i32) -> bool {
+        let below_floor = self
+            .floor_depth(lowest_rock)
+            .is_some_and(|depth| point.y >= depth);
+        let outside_walls = self
+            .walls
+            .is_some_and(|(left, right)| point.x <= left || point.x >= right);
+
+        below_floor || outside_walls
+    }
+
+    pub fn loses(&self, point: &Point2d<i32>, lowest_rock: i32) -> bool {
+        self.floor == Floor::Abyss && point.y > lowest_rock
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floor_depth() {
+        let expected = vec![None, Some(4), Some(11)];
+
+        let result: Vec<Option<i32>> = [Floor::Abyss, Floor::At(4), Floor::BelowLowestRock(2)]
+            .iter()
+            .map(|&floor| Boundary { floor, walls: None }.floor_depth(9))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let boundary = Boundary {
+            floor: Floor::At(10),
+            walls: Some((495, 505)),
+        };
+
+        let expected = vec![false, true, true, true, false];
+
+        let result: Vec<bool> = [(500, 9), (500, 10), (495, 0), (505, 0), (496, 0)]
+            .iter()
+            .map(|&point| boundary.blocks(&Point2d::from(point), 9))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_loses() {
+        let abyss = Boundary::default();
+        let floor = Boundary {
+            floor: Floor::BelowLowestRock(2),
+            walls: None,
+        };
+
+        let expected = vec![false, true, false];
+
+        let result = vec![
+            abyss.loses(&Point2d::new(500, 9), 9),
+            abyss.loses(&Point2d::new(500, 10), 9),
+            floor.loses(&Point2d::new(500, 10), 9),
+        ];
+
+        assert_eq!(result, expected);
+    }
+}
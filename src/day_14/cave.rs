@@ -1,102 +1,313 @@
-use std::collections::HashSet;
-
+use super::boundary::Boundary;
+use crate::util::grid::Grid;
 use crate::util::point_2d::Point2d;
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
+
+/*
+The tiles cover every rock plus the triangle of sand that can pile up from each source
+onto the floor, or just below the lowest rock without one, so sand never has to be
+tracked outside the grid.
+
+Every grain follows the previous one from its source until the point where the previous
+one came to rest, so `falling_paths` keeps the route of the last grain from each source
+and the next grain carries on from the step before it.
+*/
 #[derive(Debug, PartialEq)]
 pub struct Cave {
-    rocks: HashSet<Point2d<i32>>,
-    sand: HashSet<Point2d<i32>>,
-    sand_starting_point: Point2d<i32>,
-    x_bounds: (i32, i32),
-    y_bounds: (i32, i32),
+    tiles: Grid<Tile>,
+    // The cave coordinates of the top left tile.
+    origin: Point2d<i32>,
+    sources: Vec<Point2d<i32>>,
+    boundary: Boundary,
+    lowest_rock: i32,
+    number_of_grains: usize,
+    falling_paths: Vec<Vec<Point2d<i32>>>,
+    next_source: usize,
 }
 
 impl Cave {
     pub fn new() -> Cave {
-        Cave {
-            rocks: HashSet::new(),
-            sand: HashSet::new(),
-            sand_starting_point: Point2d::new(500, 0),
-            x_bounds: (0, 0),
-            y_bounds: (0, 0),
-        }
+        Cave::with_sources(&[Point2d::new(500, 0)])
+    }
+
+    pub fn with_sources(sources: &[Point2d<i32>]) -> Cave {
+        let mut result = Cave {
+            tiles: Grid::new(0, 0, Tile::Air),
+            origin: sources.first().cloned().unwrap_or(Point2d::new(500, 0)),
+            sources: vec![],
+            boundary: Boundary::default(),
+            lowest_rock: 0,
+            number_of_grains: 0,
+            falling_paths: vec![],
+            next_source: 0,
+        };
+
+        sources.iter().for_each(|source| result.add_source(*source));
+
+        result
     }
 
     pub fn add_rock(&mut self, rock_definition: &str) {
-        self.rocks.extend(points_along(rock_definition).iter());
+        let rocks = points_along(rock_definition);
 
-        for point in self.rocks.iter() {
-            if point.x < self.x_bounds.0 {
-                self.x_bounds.0 = point.x;
-            }
+        self.lowest_rock = rocks
+            .iter()
+            .map(|point| point.y)
+            .fold(self.lowest_rock, i32::max);
+        self.cover(&rocks);
+        self.falling_paths.iter_mut().for_each(Vec::clear);
 
-            if point.x > self.x_bounds.1 {
-                self.x_bounds.1 = point.x;
-            }
+        for rock in rocks {
+            self.set_tile(&rock, Tile::Rock);
+        }
+    }
 
-            if point.y < self.y_bounds.0 {
-                self.y_bounds.0 = point.y;
-            }
+    pub fn add_source(&mut self, source: Point2d<i32>) {
+        self.sources.push(source);
+        self.falling_paths.push(vec![]);
+        self.cover(&[source]);
+    }
+
+    // Keeps the sand that already came to rest, so a scenario can carry on from the last one.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+        self.falling_paths.iter_mut().for_each(Vec::clear);
+        self.cover(&[]);
+    }
 
-            if point.y > self.y_bounds.1 {
-                self.y_bounds.1 = point.y;
+    // Leaves only the rocks, to run another scenario on the same layout.
+    pub fn clear_sand(&mut self) {
+        self.tiles = self.tiles.map(|tile| match tile {
+            Tile::Sand => Tile::Air,
+            tile => *tile,
+        });
+        self.number_of_grains = 0;
+        self.falling_paths.iter_mut().for_each(Vec::clear);
+        self.next_source = 0;
+    }
+
+    /*
+    The sources take turns, skipping any whose grain is lost or which is already covered.
+    `None` once no source can add a grain that comes to rest.
+    */
+    pub fn drop_sand(&mut self) -> Option<Point2d<i32>> {
+        let number_of_sources = self.sources.len();
+
+        for offset in 0..number_of_sources {
+            let index = (self.next_source + offset) % number_of_sources;
+
+            if let Some(point) = self.drop_sand_from(index) {
+                self.next_source = (index + 1) % number_of_sources;
+
+                return Some(point);
             }
         }
-    }
 
-    pub fn drop_sand(&mut self, use_floor: bool) -> Option<Point2d<i32>> {
-        let mut current_point = self.sand_starting_point.clone();
+        None
+    }
 
-        while self.is_in_bounds(&current_point, use_floor) {
-            let point_down = current_point.add_t((0, 1));
-            let point_down_and_left = current_point.add_t((-1, 1));
-            let point_down_and_right = current_point.add_t((1, 1));
+    pub fn drop_sand_from(&mut self, source_index: usize) -> Option<Point2d<i32>> {
+        let source = *self.sources.get(source_index)?;
+        let mut falling_path = std::mem::take(&mut self.falling_paths[source_index]);
 
-            if !self.is_occupied(&point_down, use_floor) {
-                current_point = point_down;
-            } else if !self.is_occupied(&point_down_and_left, use_floor) {
-                current_point = point_down_and_left;
-            } else if !self.is_occupied(&point_down_and_right, use_floor) {
-                current_point = point_down_and_right;
-            } else {
-                break;
+        if falling_path.is_empty() {
+            if self.is_occupied(&source) {
+                return None;
             }
+
+            falling_path.push(source);
         }
 
-        let starting_point_at_rest = self.sand.contains(&self.sand_starting_point);
+        let result = loop {
+            let current_point = *falling_path.last().unwrap();
+
+            if self.is_lost(&current_point) {
+                break None;
+            }
 
-        if self.is_in_bounds(&current_point, use_floor) && !starting_point_at_rest {
-            self.sand.insert(current_point.clone());
+            let next_point = [(0, 1), (-1, 1), (1, 1)]
+                .iter()
+                .map(|step| current_point.add_t(*step))
+                .find(|point| !self.is_occupied(point));
 
-            Some(current_point)
-        } else {
-            None
+            match next_point {
+                Some(point) => falling_path.push(point),
+                None => {
+                    falling_path.pop();
+                    self.set_tile(&current_point, Tile::Sand);
+                    self.number_of_grains += 1;
+
+                    break Some(current_point);
+                }
+            }
+        };
+
+        self.falling_paths[source_index] = falling_path;
+
+        // Grains from other sources are only valid up to the tile that just filled up.
+        if let Some(point) = result {
+            for path in self.falling_paths.iter_mut() {
+                if let Some(position) = path.iter().position(|step| *step == point) {
+                    path.truncate(position);
+                }
+            }
         }
+
+        result
     }
 
     pub fn number_of_grains(&self) -> usize {
-        self.sand.len()
+        self.number_of_grains
     }
 
-    fn is_in_bounds(&self, point: &Point2d<i32>, use_floor: bool) -> bool {
-        let in_x_bounds = (self.x_bounds.0 <= point.x) && (point.x <= self.x_bounds.1);
-        let in_y_bounds = (self.y_bounds.0 <= point.y) && (point.y <= self.y_bounds.1);
+    /*
+    Rocks as `#`, sand as `o`, sources as `+` and air as `.`, like the puzzle's diagrams,
+    cropped to the rocks, sand and sources. A floor is drawn as a row of `#` and walls as
+    columns of `|`.
+    */
+    #[cfg(test)]
+    pub fn render(&self) -> String {
+        let floor = self.boundary.floor_depth(self.lowest_rock);
+        let walls = self
+            .boundary
+            .walls
+            .map(|(left, right)| vec![left, right])
+            .unwrap_or_default();
+
+        let occupied: Vec<Point2d<i32>> = self
+            .tiles
+            .iter()
+            .filter(|(_, tile)| **tile != Tile::Air)
+            .map(|(point, _)| point.add_t((self.origin.x, self.origin.y)))
+            .chain(self.sources.iter().cloned())
+            .chain(walls.iter().map(|x| Point2d::new(*x, self.origin.y.max(0))))
+            .collect();
+
+        let min_x = occupied.iter().map(|point| point.x).min().unwrap();
+        let max_x = occupied.iter().map(|point| point.x).max().unwrap();
+        let min_y = occupied.iter().map(|point| point.y).min().unwrap();
+        let max_y = occupied
+            .iter()
+            .map(|point| point.y)
+            .chain(floor)
+            .max()
+            .unwrap();
+
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| {
+                        let point = Point2d::new(x, y);
+
+                        match self.tile_at(&point) {
+                            Tile::Rock => '#',
+                            Tile::Sand => 'o',
+                            Tile::Air if floor == Some(y) => '#',
+                            Tile::Air if walls.contains(&x) => '|',
+                            Tile::Air if self.sources.contains(&point) => '+',
+                            Tile::Air => '.',
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
 
-        if use_floor {
-            (self.y_bounds.0 <= point.y) && (point.y <= (self.y_bounds.1 + 2))
-        } else {
-            in_x_bounds && in_y_bounds
-        }
+    fn is_lost(&self, point: &Point2d<i32>) -> bool {
+        self.boundary.loses(point, self.lowest_rock)
+    }
+
+    fn is_occupied(&self, point: &Point2d<i32>) -> bool {
+        self.tile_at(point) != Tile::Air || self.boundary.blocks(point, self.lowest_rock)
     }
 
-    fn is_occupied(&self, point: &Point2d<i32>, use_floor: bool) -> bool {
-        let is_rock_or_sand = self.rocks.contains(&point) || self.sand.contains(&point);
+    fn tile_at(&self, point: &Point2d<i32>) -> Tile {
+        self.tiles
+            .get(&self.to_grid(point))
+            .cloned()
+            .unwrap_or(Tile::Air)
+    }
+
+    fn set_tile(&mut self, point: &Point2d<i32>, tile: Tile) {
+        let grid_point = self.to_grid(point);
 
-        if use_floor {
-            is_rock_or_sand || (point.y == self.y_bounds.1 + 2)
+        self.tiles.set(&grid_point, tile);
+    }
+
+    fn to_grid(&self, point: &Point2d<i32>) -> Point2d<i32> {
+        point.add_t((-self.origin.x, -self.origin.y))
+    }
+
+    // Grows the tiles to fit `points` and the sand triangle below every source.
+    fn cover(&mut self, points: &[Point2d<i32>]) {
+        let deepest = self
+            .boundary
+            .floor_depth(self.lowest_rock)
+            .unwrap_or(self.lowest_rock + 1);
+        let old_corner = self.origin.add_t((
+            self.tiles.width() as i32 - 1,
+            self.tiles.height() as i32 - 1,
+        ));
+
+        let triangles: Vec<Point2d<i32>> = self
+            .sources
+            .iter()
+            .flat_map(|source| {
+                let spread = (deepest - source.y).max(0) + 1;
+
+                [
+                    source.add_t((-spread, 0)),
+                    source.add_t((spread, 0)),
+                    *source,
+                ]
+            })
+            .collect();
+        let corners = if self.tiles.is_empty() {
+            vec![]
         } else {
-            is_rock_or_sand
+            vec![self.origin, old_corner]
+        };
+        let all_points = || points.iter().chain(&triangles).chain(&corners);
+
+        let Some(min_x) = all_points().map(|point| point.x).min() else {
+            return;
+        };
+        let max_x = all_points().map(|point| point.x).max().unwrap();
+        let min_y = all_points().map(|point| point.y).min().unwrap();
+        let max_y = all_points()
+            .map(|point| point.y)
+            .chain([deepest])
+            .max()
+            .unwrap();
+
+        let origin = Point2d::new(min_x, min_y);
+        let mut tiles = Grid::new(
+            (max_x - min_x + 1) as usize,
+            (max_y - min_y + 1) as usize,
+            Tile::Air,
+        );
+
+        for (point, tile) in self.tiles.iter() {
+            let cave_point = point.add_t((self.origin.x, self.origin.y));
+
+            tiles.set(&cave_point.add_t((-origin.x, -origin.y)), *tile);
         }
+
+        self.tiles = tiles;
+        self.origin = origin;
+    }
+}
+
+impl Default for Cave {
+    fn default() -> Cave {
+        Cave::new()
     }
 }
 
@@ -152,6 +363,15 @@ fn points_along(path: &str) -> Vec<Point2d<i32>> {
 mod tests {
     use super::*;
 
+    use crate::day_14::boundary::Floor;
+
+    fn puzzle_floor() -> Boundary {
+        Boundary {
+            floor: Floor::BelowLowestRock(2),
+            walls: None,
+        }
+    }
+
     #[test]
     fn test_point_from() {
         let input = "498,4";
@@ -211,7 +431,7 @@ mod tests {
 
         let expected = Some(Point2d::new(500, 8));
 
-        let result = cave.drop_sand(false);
+        let result = cave.drop_sand();
 
         assert_eq!(result, expected);
     }
@@ -228,12 +448,12 @@ mod tests {
         input.iter().for_each(|line| cave.add_rock(&line));
 
         (0..23).for_each(|_| {
-            cave.drop_sand(false);
+            cave.drop_sand();
         });
 
         let expected = Some(Point2d::new(495, 8));
 
-        let result = cave.drop_sand(false);
+        let result = cave.drop_sand();
 
         assert_eq!(result, expected);
     }
@@ -250,12 +470,12 @@ mod tests {
         input.iter().for_each(|line| cave.add_rock(&line));
 
         (0..24).for_each(|_| {
-            cave.drop_sand(false);
+            cave.drop_sand();
         });
 
         let expected = None;
 
-        let result = cave.drop_sand(false);
+        let result = cave.drop_sand();
 
         assert_eq!(result, expected);
     }
@@ -270,14 +490,269 @@ mod tests {
         let mut cave = Cave::new();
 
         input.iter().for_each(|line| cave.add_rock(&line));
+        cave.set_boundary(puzzle_floor());
 
         (0..24).for_each(|_| {
-            cave.drop_sand(true);
+            cave.drop_sand();
         });
 
         let expected = Some(Point2d::new(493, 10));
 
-        let result = cave.drop_sand(true);
+        let result = cave.drop_sand();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::new();
+
+        input.iter().for_each(|line| cave.add_rock(line));
+
+        let expected = [
+            "......+...",
+            "..........",
+            "..........",
+            "..........",
+            "....#...##",
+            "....#...#.",
+            "..###...#.",
+            "........#.",
+            "........#.",
+            "#########.",
+        ]
+        .join("\n");
+
+        let result = cave.render();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_render_after_sand_comes_to_rest() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::new();
+
+        input.iter().for_each(|line| cave.add_rock(line));
+
+        while cave.drop_sand().is_some() {}
+
+        let expected = [
+            "......+...",
+            "..........",
+            "......o...",
+            ".....ooo..",
+            "....#ooo##",
+            "...o#ooo#.",
+            "..###ooo#.",
+            "....oooo#.",
+            ".o.ooooo#.",
+            "#########.",
+        ]
+        .join("\n");
+
+        let result = cave.render();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_fast_drop_matches_restarting_from_the_source() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::new();
+        let mut other_cave = Cave::new();
+
+        input.iter().for_each(|line| {
+            cave.add_rock(line);
+            other_cave.add_rock(line);
+        });
+        cave.set_boundary(puzzle_floor());
+        other_cave.set_boundary(puzzle_floor());
+
+        let expected: Vec<Option<Point2d<i32>>> = (0..100)
+            .map(|_| {
+                let result = other_cave.drop_sand();
+
+                other_cave.falling_paths[0].clear();
+
+                result
+            })
+            .collect();
+
+        let result: Vec<Option<Point2d<i32>>> = (0..100).map(|_| cave.drop_sand()).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_sources_take_turns() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::with_sources(&[Point2d::new(500, 0), Point2d::new(493, 0)]);
+
+        input.iter().for_each(|line| cave.add_rock(line));
+        cave.set_boundary(puzzle_floor());
+
+        let expected = vec![
+            Some(Point2d::new(500, 8)),
+            Some(Point2d::new(493, 10)),
+            Some(Point2d::new(499, 8)),
+            Some(Point2d::new(492, 10)),
+        ];
+
+        let result: Vec<Option<Point2d<i32>>> = (0..4).map(|_| cave.drop_sand()).collect();
+
+        assert_eq!(result, expected);
+
+        while cave.drop_sand().is_some() {}
+
+        assert_eq!(cave.number_of_grains(), 158);
+    }
+
+    #[test]
+    fn test_source_losing_sand_is_skipped() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::with_sources(&[Point2d::new(493, 0), Point2d::new(500, 0)]);
+
+        input.iter().for_each(|line| cave.add_rock(line));
+
+        let expected = 24;
+
+        while cave.drop_sand().is_some() {}
+
+        let result = cave.number_of_grains();
+
+        assert_eq!(result, expected);
+        assert_eq!(cave.drop_sand_from(0), None);
+    }
+
+    #[test]
+    fn test_render_with_walls_and_floor() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::new();
+
+        input.iter().for_each(|line| cave.add_rock(line));
+        cave.set_boundary(Boundary {
+            floor: Floor::BelowLowestRock(2),
+            walls: Some((495, 504)),
+        });
+
+        while cave.drop_sand().is_some() {}
+
+        let expected = [
+            ".|....o...|",
+            ".|...ooo..|",
+            ".|..ooooo.|",
+            ".|.ooooooo|",
+            ".|oo#ooo##|",
+            ".|oo#ooo#.|",
+            ".|###ooo#.|",
+            ".|..oooo#.|",
+            ".|.ooooo#.|",
+            "#########.|",
+            ".|........|",
+            "###########",
+        ]
+        .join("\n");
+
+        let result = cave.render();
+
+        assert_eq!(result, expected);
+        assert_eq!(cave.number_of_grains(), 38);
+    }
+
+    #[test]
+    fn test_scenarios_on_the_same_rocks() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+
+        let mut cave = Cave::new();
+
+        input.iter().for_each(|line| cave.add_rock(line));
+
+        let expected = vec![24, 93, 53];
+
+        let result: Vec<usize> = [
+            Boundary::default(),
+            puzzle_floor(),
+            Boundary {
+                floor: Floor::At(8),
+                walls: None,
+            },
+        ]
+        .iter()
+        .map(|boundary| {
+            cave.clear_sand();
+            cave.set_boundary(*boundary);
+
+            while cave.drop_sand().is_some() {}
+
+            cave.number_of_grains()
+        })
+        .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_fast_drop_with_several_sources_matches_restarting() {
+        let input = [
+            String::from("498,4 -> 498,6 -> 496,6"),
+            String::from("503,4 -> 502,4 -> 502,9 -> 494,9"),
+        ];
+        let sources = [
+            Point2d::new(500, 0),
+            Point2d::new(497, 1),
+            Point2d::new(503, 2),
+        ];
+
+        let mut cave = Cave::with_sources(&sources);
+        let mut other_cave = Cave::with_sources(&sources);
+
+        input.iter().for_each(|line| {
+            cave.add_rock(line);
+            other_cave.add_rock(line);
+        });
+        cave.set_boundary(puzzle_floor());
+        other_cave.set_boundary(puzzle_floor());
+
+        let expected: Vec<Option<Point2d<i32>>> = (0..150)
+            .map(|_| {
+                let result = other_cave.drop_sand();
+
+                other_cave.falling_paths.iter_mut().for_each(Vec::clear);
+
+                result
+            })
+            .collect();
+
+        let result: Vec<Option<Point2d<i32>>> = (0..150).map(|_| cave.drop_sand()).collect();
 
         assert_eq!(result, expected);
     }
@@ -1,7 +1,9 @@
+mod boundary;
 mod cave;
 
 use crate::util::file_reader::to_string_vector;
 
+use boundary::{Boundary, Floor};
 use cave::Cave;
 
 pub fn run() {
@@ -20,7 +22,9 @@ pub fn run() {
 fn part_1(cave: &mut Cave) -> usize {
     let mut number_of_grains = 0;
 
-    while cave.drop_sand(false).is_some() {
+    cave.set_boundary(Boundary::default());
+
+    while cave.drop_sand().is_some() {
         number_of_grains += 1;
     }
 
@@ -28,7 +32,13 @@ fn part_1(cave: &mut Cave) -> usize {
 }
 
 fn part_2(cave: &mut Cave) -> usize {
-    while cave.drop_sand(true).is_some() {}
+    cave.clear_sand();
+    cave.set_boundary(Boundary {
+        floor: Floor::BelowLowestRock(2),
+        walls: None,
+    });
+
+    while cave.drop_sand().is_some() {}
 
     cave.number_of_grains()
 }
@@ -1,6 +1,6 @@
 use crate::util::file_reader::to_string_vector;
 
-use crate::device::Device;
+use crate::device::{Device, DeviceBuilder};
 
 pub fn run() {
     let input = to_string_vector("inputs/day_7.txt").expect("Something went wrong Day 7!");
@@ -12,11 +12,10 @@ pub fn run() {
 }
 
 fn get_device(input: &[String]) -> Device {
-    let mut device = Device::new();
-
-    device.populate_file_system_from_executed_commands(input);
-
-    device
+    DeviceBuilder::new()
+        .executed_commands(input)
+        .build()
+        .expect("Something went wrong Day 7!")
 }
 
 fn part_1(device: &Device) -> u64 {
@@ -1,5 +1,8 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::util::grid::Grid;
+use crate::util::point_2d::Point2d;
+
 #[derive(Debug, Hash, Copy, Clone, PartialEq, Eq)]
 pub enum Direction {
     Top,
@@ -8,178 +11,112 @@ pub enum Direction {
     Left,
 }
 
+impl Direction {
+    fn step(&self) -> (i32, i32) {
+        match self {
+            Direction::Top => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Bottom => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+}
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Top,
+    Direction::Right,
+    Direction::Bottom,
+    Direction::Left,
+];
+
 #[derive(Debug, PartialEq)]
 pub struct Forest {
-    trees: Vec<Vec<u32>>,
-    length: usize,
-    width: usize,
+    trees: Grid<u32>,
 }
 
 impl Forest {
     pub fn tree_visibility(&self) -> HashMap<(usize, usize), HashSet<Direction>> {
-        let mut result = HashMap::new();
+        let mut result: HashMap<(usize, usize), HashSet<Direction>> = self
+            .trees
+            .points()
+            .map(|point| (location_of(&point), HashSet::new()))
+            .collect();
 
-        for row in 0..self.length {
-            for col in 0..self.width {
-                result.entry((row, col)).or_insert(HashSet::new());
+        for direction in DIRECTIONS {
+            for location in self.tree_visibility_from(direction) {
+                result.entry(location).and_modify(|directions| {
+                    directions.insert(direction);
+                });
             }
         }
 
-        for location in self.tree_visibility_from(Direction::Top) {
-            result.entry(location).and_modify(|directions| {
-                directions.insert(Direction::Top);
-            });
-        }
-
-        for location in self.tree_visibility_from(Direction::Right) {
-            result.entry(location).and_modify(|directions| {
-                directions.insert(Direction::Right);
-            });
-        }
-
-        for location in self.tree_visibility_from(Direction::Bottom) {
-            result.entry(location).and_modify(|directions| {
-                directions.insert(Direction::Bottom);
-            });
-        }
-
-        for location in self.tree_visibility_from(Direction::Left) {
-            result.entry(location).and_modify(|directions| {
-                directions.insert(Direction::Left);
-            });
-        }
-
         result
     }
 
     pub fn tree_scenic_scores(&self) -> HashMap<(usize, usize), usize> {
-        let mut result = HashMap::new();
-
-        for (location, score) in self.tree_scenic_scores_from(Direction::Top) {
-            result.entry(location).or_insert(score);
-        }
-
-        for (location, score) in self.tree_scenic_scores_from(Direction::Right) {
-            result.entry(location).and_modify(|value| *value *= score);
-        }
-
-        for (location, score) in self.tree_scenic_scores_from(Direction::Bottom) {
-            result.entry(location).and_modify(|value| *value *= score);
-        }
+        let mut result: HashMap<(usize, usize), usize> = self
+            .trees
+            .points()
+            .map(|point| (location_of(&point), 1))
+            .collect();
 
-        for (location, score) in self.tree_scenic_scores_from(Direction::Left) {
-            result.entry(location).and_modify(|value| *value *= score);
+        for direction in DIRECTIONS {
+            for (location, score) in self.tree_scenic_scores_from(direction) {
+                result.entry(location).and_modify(|value| *value *= score);
+            }
         }
 
         result
     }
 
+    // The trees that are taller than every tree between them and the edge in `direction`.
     fn tree_visibility_from(&self, direction: Direction) -> HashSet<(usize, usize)> {
-        let mut result = HashSet::new();
-
-        let mut max_height_so_far = None;
-
-        for count in 0..(self.length * self.width) {
-            let (row, col) = self.next_tree_from(direction, count);
-
-            if self.line_count(direction, count) == 0 {
-                max_height_so_far = None;
-            }
-
-            let tree_height = *self
-                .trees
-                .get(row)
-                .map(|row| row.get(col))
-                .flatten()
-                .expect(&format!("Couldn't get tree at: {:?}", (row, col)));
-
-            match max_height_so_far {
-                Some(max_height) if tree_height <= max_height => (),
-                _ => {
-                    result.insert((row, col));
-                    max_height_so_far = Some(tree_height);
-                }
-            }
-        }
-
-        result
+        self.trees
+            .iter()
+            .filter(|(point, tree_height)| {
+                self.trees
+                    .ray(point, direction.step())
+                    .all(|(_, height)| height < tree_height)
+            })
+            .map(|(point, _)| location_of(&point))
+            .collect()
     }
 
+    // How many trees can be seen in `direction` before the view is blocked or the edge is reached.
     fn tree_scenic_scores_from(&self, direction: Direction) -> HashMap<(usize, usize), usize> {
-        let mut result = HashMap::new();
-
-        let mut heights_last_seen = HashMap::new();
-
-        for tree in 0..(self.length * self.width) {
-            let (row, col) = self.next_tree_from(direction, tree);
-
-            let tree_in_line = self.line_count(direction, tree);
-
-            if tree_in_line == 0 {
-                heights_last_seen = HashMap::new();
-            }
-
-            let tree_height = *self
-                .trees
-                .get(row)
-                .map(|line| line.get(col))
-                .flatten()
-                .expect(&format!("Couldn't get tree at: {:?}", (row, col)));
-
-            let score = (tree_height..10)
-                .filter_map(|height| {
-                    heights_last_seen
-                        .get(&height)
-                        .map(|last_seen| tree_in_line - last_seen)
-                })
-                .min()
-                .unwrap_or(tree_in_line);
-
-            result.insert((row, col), score);
-
-            heights_last_seen.insert(tree_height, tree_in_line);
-        }
+        self.trees
+            .iter()
+            .map(|(point, tree_height)| {
+                let mut score = 0;
 
-        result
-    }
+                for (_, height) in self.trees.ray(&point, direction.step()) {
+                    score += 1;
 
-    fn next_tree_from(&self, direction: Direction, count: usize) -> (usize, usize) {
-        match direction {
-            Direction::Top => (count % self.length, count / self.length),
-            Direction::Right => (count / self.width, self.width - 1 - count % self.width),
-            Direction::Bottom => (self.length - 1 - count % self.length, count / self.length),
-            Direction::Left => (count / self.width, count % self.width),
-        }
-    }
+                    if height >= tree_height {
+                        break;
+                    }
+                }
 
-    fn line_count(&self, direction: Direction, count: usize) -> usize {
-        match direction {
-            Direction::Top => count % self.length,
-            Direction::Right => count % self.width,
-            Direction::Bottom => count % self.length,
-            Direction::Left => count % self.width,
-        }
+                (location_of(&point), score)
+            })
+            .collect()
     }
 }
 
 impl From<&Vec<String>> for Forest {
     fn from(input: &Vec<String>) -> Forest {
-        let trees: Vec<Vec<u32>> = input
-            .iter()
-            .map(|line| line.chars().map(|c| c.to_digit(10).unwrap()).collect())
-            .collect();
-
-        let length = trees.len();
-        let width = trees.get(0).map_or(0, |row| row.len());
-
         Forest {
-            trees,
-            length,
-            width,
+            trees: Grid::parse(input, |c| c.to_digit(10))
+                .unwrap_or_else(|error| panic!("{}", error)),
         }
     }
 }
 
+// Locations are reported as (row, column).
+fn location_of(point: &Point2d<i32>) -> (usize, usize) {
+    (point.y as usize, point.x as usize)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,15 +132,14 @@ mod tests {
         ];
 
         let expected = Forest {
-            trees: vec![
+            trees: Grid::from_rows(vec![
                 vec![3, 0, 3, 7, 3],
                 vec![2, 5, 5, 1, 2],
                 vec![6, 5, 3, 3, 2],
                 vec![3, 3, 5, 4, 9],
                 vec![3, 5, 3, 9, 0],
-            ],
-            width: 5,
-            length: 5,
+            ])
+            .unwrap(),
         };
 
         let result = Forest::from(&input);
@@ -0,0 +1,328 @@
+use super::commands::{self, ExecutedCommand};
+use super::file_system::FileSystem;
+use super::video_system::{CpuInstruction, VideoSystem};
+use super::{Device, FramingConfig};
+
+const DATA_STREAM_SECTION: &str = "data_stream";
+const FRAMING_SECTION: &str = "framing";
+const FILE_SYSTEM_SECTION: &str = "file_system";
+const VIDEO_PROGRAM_SECTION: &str = "video_program";
+
+#[derive(Debug, PartialEq, Default)]
+pub struct DeviceBuilder {
+    data_stream: Vec<char>,
+    framing_config: FramingConfig,
+    executed_commands: Vec<String>,
+    video_program: Vec<String>,
+}
+
+impl DeviceBuilder {
+    pub fn new() -> DeviceBuilder {
+        DeviceBuilder::default()
+    }
+
+    /*
+    A bundle is a list of sections, each started by a `[name]` header line:
+
+        [data_stream]
+        mjqjpqmgbljsphdztnvjfqwrcgsmlb
+        [framing]
+        packet_marker_size = 4
+        message_marker_size = 14
+        [file_system]
+        $ cd /
+        ...
+        [video_program]
+        noop
+        ...
+
+    Every section is optional and blank lines are ignored. A data stream split across
+    several lines is joined back together.
+    */
+    pub fn from_bundle(input: &[String]) -> Result<DeviceBuilder, String> {
+        let mut builder = DeviceBuilder::new();
+        let mut seen_sections: Vec<String> = vec![];
+
+        for (line_number, line) in input.iter().enumerate().map(|(i, line)| (i + 1, line)) {
+            let line = line.trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                if ![
+                    DATA_STREAM_SECTION,
+                    FRAMING_SECTION,
+                    FILE_SYSTEM_SECTION,
+                    VIDEO_PROGRAM_SECTION,
+                ]
+                .contains(&name)
+                {
+                    return Err(format!(
+                        "Unknown section [{}] on line {}",
+                        name, line_number
+                    ));
+                }
+
+                if seen_sections.iter().any(|seen| seen == name) {
+                    return Err(format!(
+                        "Section [{}] on line {} appears more than once",
+                        name, line_number
+                    ));
+                }
+
+                seen_sections.push(name.to_string());
+
+                continue;
+            }
+
+            match seen_sections.last().map(|name| name.as_str()) {
+                Some(DATA_STREAM_SECTION) => builder.data_stream.extend(line.chars()),
+                Some(FRAMING_SECTION) => builder.set_framing_value(line, line_number)?,
+                Some(FILE_SYSTEM_SECTION) => {
+                    commands::validate_line(line, !builder.executed_commands.is_empty())
+                        .map_err(|error| section_error(FILE_SYSTEM_SECTION, line_number, &error))?;
+
+                    builder.executed_commands.push(line.to_string())
+                }
+                Some(VIDEO_PROGRAM_SECTION) => {
+                    line.parse::<CpuInstruction>().map_err(|error| {
+                        section_error(VIDEO_PROGRAM_SECTION, line_number, &error)
+                    })?;
+
+                    builder.video_program.push(line.to_string())
+                }
+                _ => {
+                    return Err(format!(
+                        "Line {} is not inside a section: {}",
+                        line_number, line
+                    ))
+                }
+            }
+        }
+
+        Ok(builder)
+    }
+
+    pub fn data_stream(mut self, input: &str) -> DeviceBuilder {
+        self.data_stream = input.chars().collect();
+        self
+    }
+
+    pub fn framing_config(mut self, config: FramingConfig) -> DeviceBuilder {
+        self.framing_config = config;
+        self
+    }
+
+    pub fn executed_commands(mut self, commands: &[String]) -> DeviceBuilder {
+        self.executed_commands = commands.to_vec();
+        self
+    }
+
+    pub fn video_program(mut self, instructions: &[String]) -> DeviceBuilder {
+        self.video_program = instructions.to_vec();
+        self
+    }
+
+    // Line numbers in errors count from the start of the commands or the program.
+    pub fn build(self) -> Result<Device, String> {
+        self.framing_config.validate()?;
+
+        for (index, line) in self.executed_commands.iter().enumerate() {
+            commands::validate_line(line, index > 0)
+                .map_err(|error| section_error(FILE_SYSTEM_SECTION, index + 1, &error))?;
+        }
+
+        for (index, line) in self.video_program.iter().enumerate() {
+            line.parse::<CpuInstruction>()
+                .map_err(|error| section_error(VIDEO_PROGRAM_SECTION, index + 1, &error))?;
+        }
+
+        let mut video_system = VideoSystem::new();
+
+        video_system.add_cpu_instructions(&self.video_program);
+
+        Ok(Device {
+            data_stream_buffer: self.data_stream,
+            framing_config: self.framing_config,
+            file_system: FileSystem::create_from_executed_commands(
+                &ExecutedCommand::extract_commands(&self.executed_commands),
+            ),
+            video_system,
+        })
+    }
+
+    fn set_framing_value(&mut self, line: &str, line_number: usize) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Expected `key = value` on line {}, found: {}",
+                    line_number, line
+                )
+            })?;
+
+        let size: usize = value
+            .parse()
+            .map_err(|_| format!("Invalid marker size on line {}: {}", line_number, value))?;
+
+        match key {
+            "packet_marker_size" => self.framing_config.packet_marker_size = size,
+            "message_marker_size" => self.framing_config.message_marker_size = size,
+            _ => {
+                return Err(format!(
+                    "Unknown framing setting on line {}: {}",
+                    line_number, key
+                ))
+            }
+        }
+
+        self.framing_config
+            .validate()
+            .map_err(|error| format!("{} on line {}", error, line_number))
+    }
+}
+
+fn section_error(section: &str, line_number: usize, error: &str) -> String {
+    format!("Invalid line {} in [{}]: {}", line_number, section, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_lines(input: &str) -> Vec<String> {
+        input.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_from_bundle() {
+        let input = to_lines(
+            "[data_stream]\n\
+             mjqjpqmgbl\n\
+             jsphdztnvjfqwrcgsmlb\n\
+             \n\
+             [framing]\n\
+             packet_marker_size = 2\n\
+             [file_system]\n\
+             $ cd /\n\
+             $ ls\n\
+             584 i\n\
+             [video_program]\n\
+             noop\n\
+             addx 3",
+        );
+
+        let expected = DeviceBuilder::new()
+            .data_stream("mjqjpqmgbljsphdztnvjfqwrcgsmlb")
+            .framing_config(FramingConfig {
+                packet_marker_size: 2,
+                message_marker_size: 14,
+            })
+            .executed_commands(&to_lines("$ cd /\n$ ls\n584 i"))
+            .video_program(&to_lines("noop\naddx 3"));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test]
+    fn test_from_bundle_unknown_section() {
+        let input = to_lines("[data_stream]\nabc\n[keyboard]");
+
+        let expected = Err(String::from("Unknown section [keyboard] on line 3"));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_line_outside_section() {
+        let input = to_lines("noop\n[video_program]");
+
+        let expected = Err(String::from("Line 1 is not inside a section: noop"));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_invalid_framing_value() {
+        let input = to_lines("[framing]\nmessage_marker_size = lots");
+
+        let expected = Err(String::from("Invalid marker size on line 2: lots"));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_zero_marker_size() {
+        let input = to_lines("[framing]\npacket_marker_size = 0");
+
+        let expected = Err(String::from("Marker sizes must be at least 1 on line 2"));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_unknown_instruction() {
+        let input = to_lines("[video_program]\nnoop\nmul 3");
+
+        let expected = Err(String::from(
+            "Invalid line 3 in [video_program]: Unknown instruction: mul 3",
+        ));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_invalid_file_listing() {
+        let input = to_lines("[data_stream]\nabc\n[file_system]\n$ cd /\n$ ls\nabc i");
+
+        let expected = Err(String::from(
+            "Invalid line 6 in [file_system]: Invalid listing: abc i",
+        ));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_from_bundle_output_before_command() {
+        let input = to_lines("[file_system]\n584 i");
+
+        let expected = Err(String::from(
+            "Invalid line 2 in [file_system]: Output before any command: 584 i",
+        ));
+
+        let result = DeviceBuilder::from_bundle(&input);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_build_invalid_program() {
+        let expected = Err(String::from(
+            "Invalid line 2 in [video_program]: Bad addx instruction: addx x",
+        ));
+
+        let result = DeviceBuilder::new()
+            .video_program(&to_lines("noop\naddx x"))
+            .build()
+            .map(|_| ());
+
+        assert_eq!(result, expected);
+    }
+}
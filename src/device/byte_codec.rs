@@ -0,0 +1,138 @@
+#[derive(Debug, PartialEq, Default)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub fn new() -> ByteWriter {
+        ByteWriter { bytes: vec![] }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend(bytes);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { bytes, position: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.position + len;
+
+        let result = self.bytes.get(self.position..end).ok_or_else(|| {
+            format!(
+                "Expected {} bytes at offset {}, but only {} remain",
+                len,
+                self.position,
+                self.bytes.len() - self.position
+            )
+        })?;
+
+        self.position = end;
+
+        Ok(result)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, String> {
+        self.read_bytes(1).map(|bytes| bytes[0])
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, String> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, String> {
+        self.read_array().map(i32::from_le_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, String> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    pub fn read_len(&mut self) -> Result<usize, String> {
+        self.read_u32().map(|len| len as usize)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        self.read_bytes(N)
+            .map(|bytes| bytes.try_into().expect("Slice has the requested length"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_then_read() {
+        let mut writer = ByteWriter::new();
+
+        writer.write_u8(7);
+        writer.write_u32(70_000);
+        writer.write_i32(-5);
+        writer.write_u64(u64::MAX);
+        writer.write_len(3);
+        writer.write_bytes(b"abc");
+
+        let bytes = writer.into_bytes();
+        let mut reader = ByteReader::new(&bytes);
+
+        assert_eq!(reader.read_u8(), Ok(7));
+        assert_eq!(reader.read_u32(), Ok(70_000));
+        assert_eq!(reader.read_i32(), Ok(-5));
+        assert_eq!(reader.read_u64(), Ok(u64::MAX));
+        assert_eq!(reader.read_len(), Ok(3));
+        assert_eq!(reader.read_bytes(3), Ok(b"abc".as_slice()));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_read_past_end() {
+        let bytes = [1, 2];
+        let mut reader = ByteReader::new(&bytes);
+
+        let expected = Err(String::from(
+            "Expected 4 bytes at offset 0, but only 2 remain",
+        ));
+
+        let result = reader.read_u32();
+
+        assert_eq!(result, expected);
+    }
+}
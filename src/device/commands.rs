@@ -20,6 +20,31 @@ impl ExecutedCommand {
     }
 }
 
+/*
+Checks a single line of terminal output before it is handed to `extract_commands`,
+which expects every session to start with a command and every listing to be a
+directory or a file size and name.
+*/
+pub fn validate_line(line: &str, after_command: bool) -> Result<(), String> {
+    if let Some(command) = line.strip_prefix("$ ") {
+        return match command.split_once(' ') {
+            None if command == "ls" => Ok(()),
+            Some(("cd", target)) if !target.is_empty() => Ok(()),
+            _ => Err(format!("Unknown command: {}", line)),
+        };
+    }
+
+    if !after_command {
+        return Err(format!("Output before any command: {}", line));
+    }
+
+    match line.split_once(' ') {
+        Some(("dir", name)) if !name.is_empty() => Ok(()),
+        Some((size, name)) if size.parse::<u64>().is_ok() && !name.is_empty() => Ok(()),
+        _ => Err(format!("Invalid listing: {}", line)),
+    }
+}
+
 impl From<&[String]> for ExecutedCommand {
     fn from(input: &[String]) -> ExecutedCommand {
         let executed_command = &input[0];
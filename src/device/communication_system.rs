@@ -1,8 +1,206 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Read};
 
 pub const START_OF_PACKET_MARKER_SIZE: usize = 4;
 pub const START_OF_MESSAGE_MARKER_SIZE: usize = 14;
 
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum MarkerKind {
+    StartOfPacket,
+    StartOfMessage,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MarkerEvent {
+    pub kind: MarkerKind,
+    pub index: usize,
+    pub size: usize,
+}
+
+impl MarkerEvent {
+    pub fn end(&self) -> usize {
+        self.index + self.size
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct UniqueWindow {
+    size: usize,
+    buffer: VecDeque<u8>,
+    counts: Vec<usize>,
+    number_of_duplicated_values: usize,
+}
+
+impl UniqueWindow {
+    fn new(size: usize) -> UniqueWindow {
+        UniqueWindow {
+            size,
+            buffer: VecDeque::with_capacity(size),
+            counts: vec![0; 256],
+            number_of_duplicated_values: 0,
+        }
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        if self.buffer.len() == self.size {
+            if let Some(oldest) = self.buffer.pop_front() {
+                self.counts[oldest as usize] -= 1;
+
+                if self.counts[oldest as usize] == 1 {
+                    self.number_of_duplicated_values -= 1;
+                }
+            }
+        }
+
+        self.counts[byte as usize] += 1;
+
+        if self.counts[byte as usize] == 2 {
+            self.number_of_duplicated_values += 1;
+        }
+
+        self.buffer.push_back(byte);
+
+        (self.buffer.len() == self.size) && (self.number_of_duplicated_values == 0)
+    }
+
+    fn reset(&mut self) {
+        self.buffer
+            .drain(..)
+            .for_each(|byte| self.counts[byte as usize] = 0);
+        self.number_of_duplicated_values = 0;
+    }
+}
+
+/*
+Markers are reported without overlapping: once a marker is found, the next one of
+the same kind has to start after it ends. The first event of each kind is always the
+same index the slice based functions below would find.
+*/
+#[derive(Debug, PartialEq)]
+pub struct MarkerDecoder {
+    windows: Vec<(MarkerKind, UniqueWindow)>,
+    offset: usize,
+}
+
+impl MarkerDecoder {
+    pub fn new() -> MarkerDecoder {
+        MarkerDecoder::with_marker_sizes(START_OF_PACKET_MARKER_SIZE, START_OF_MESSAGE_MARKER_SIZE)
+            .expect("The puzzle's marker sizes are valid")
+    }
+
+    // An empty window can never fill up, so a marker size of 0 would never report anything.
+    pub fn with_marker_sizes(
+        packet_marker_size: usize,
+        message_marker_size: usize,
+    ) -> Result<MarkerDecoder, String> {
+        if packet_marker_size == 0 || message_marker_size == 0 {
+            return Err(String::from("Marker sizes must be at least 1"));
+        }
+
+        Ok(MarkerDecoder {
+            windows: vec![
+                (
+                    MarkerKind::StartOfPacket,
+                    UniqueWindow::new(packet_marker_size),
+                ),
+                (
+                    MarkerKind::StartOfMessage,
+                    UniqueWindow::new(message_marker_size),
+                ),
+            ],
+            offset: 0,
+        })
+    }
+
+    pub fn bytes_decoded(&self) -> usize {
+        self.offset
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<MarkerEvent> {
+        let mut result = vec![];
+
+        for &byte in chunk {
+            self.offset += 1;
+
+            for (kind, window) in self.windows.iter_mut() {
+                if window.push(byte) {
+                    result.push(MarkerEvent {
+                        kind: *kind,
+                        index: self.offset - window.size,
+                        size: window.size,
+                    });
+
+                    window.reset();
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl Default for MarkerDecoder {
+    fn default() -> MarkerDecoder {
+        MarkerDecoder::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct MarkerEvents<R: Read> {
+    reader: R,
+    decoder: MarkerDecoder,
+    chunk: Vec<u8>,
+    pending_events: VecDeque<MarkerEvent>,
+    reached_end: bool,
+}
+
+impl<R: Read> MarkerEvents<R> {
+    pub fn new(reader: R, decoder: MarkerDecoder) -> MarkerEvents<R> {
+        MarkerEvents::with_chunk_size(reader, decoder, DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(
+        reader: R,
+        decoder: MarkerDecoder,
+        chunk_size: usize,
+    ) -> MarkerEvents<R> {
+        MarkerEvents {
+            reader,
+            decoder,
+            chunk: vec![0; chunk_size.max(1)],
+            pending_events: VecDeque::new(),
+            reached_end: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for MarkerEvents<R> {
+    type Item = io::Result<MarkerEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending_events.is_empty() && !self.reached_end {
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => self.reached_end = true,
+                Ok(bytes_read) => {
+                    let events = self.decoder.feed(&self.chunk[..bytes_read]);
+
+                    self.pending_events.extend(events);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => {
+                    self.reached_end = true;
+
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        self.pending_events.pop_front().map(Ok)
+    }
+}
+
 pub fn start_of_packet_marker_index(data: &[char], data_start_index: usize) -> Option<usize> {
     start_of_n_unique_chars_index(data, data_start_index, START_OF_PACKET_MARKER_SIZE)
 }
@@ -23,6 +221,9 @@ fn start_of_n_unique_chars_index(data: &[char], data_start: usize, n: usize) ->
 mod tests {
     use super::*;
 
+    use crate::device::stream_encoder::DataStreamEncoder;
+    use crate::util::random::Random;
+
     #[test]
     fn test_start_of_packet_marker_index() {
         let input = [
@@ -85,4 +286,169 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_unique_window_push() {
+        let mut window = UniqueWindow::new(3);
+
+        let expected = vec![false, false, false, true, true, true, false, false];
+
+        let result: Vec<bool> = "aabcdbbc".bytes().map(|byte| window.push(byte)).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_decoder_first_events_match_slice_search() {
+        let input = [
+            String::from("mjqjpqmgbljsphdztnvjfqwrcgsmlb"),
+            String::from("bvwbjplbgvbhsrlpgdmjqwftvncz"),
+            String::from("nppdvjthqldpwncqszvftbrmjlhg"),
+            String::from("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"),
+            String::from("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"),
+        ];
+
+        let expected = vec![(3, 5), (1, 9), (2, 9), (6, 15), (7, 12)];
+
+        let result: Vec<(usize, usize)> = input
+            .iter()
+            .map(|line| MarkerDecoder::new().feed(line.as_bytes()))
+            .map(|events| {
+                let first_index_of = |kind| {
+                    events
+                        .iter()
+                        .find(|event| event.kind == kind)
+                        .map(|event| event.index)
+                        .unwrap()
+                };
+
+                (
+                    first_index_of(MarkerKind::StartOfPacket),
+                    first_index_of(MarkerKind::StartOfMessage),
+                )
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_decoder_feed_in_chunks() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb".as_bytes();
+
+        let expected = MarkerDecoder::new().feed(input);
+
+        let mut decoder = MarkerDecoder::new();
+        let result: Vec<MarkerEvent> = input
+            .chunks(3)
+            .flat_map(|chunk| decoder.feed(chunk))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(decoder.bytes_decoded(), input.len());
+    }
+
+    #[test]
+    fn test_marker_decoder_events_do_not_overlap() {
+        let mut decoder = MarkerDecoder::with_marker_sizes(2, 3).unwrap();
+
+        let expected = vec![
+            MarkerEvent {
+                kind: MarkerKind::StartOfPacket,
+                index: 1,
+                size: 2,
+            },
+            MarkerEvent {
+                kind: MarkerKind::StartOfMessage,
+                index: 1,
+                size: 3,
+            },
+            MarkerEvent {
+                kind: MarkerKind::StartOfPacket,
+                index: 3,
+                size: 2,
+            },
+        ];
+
+        let result = decoder.feed(b"aabcd");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_marker_events_from_reader() {
+        let input = "bvwbjplbgvbhsrlpgdmjqwftvncz".as_bytes();
+
+        let expected: Vec<MarkerEvent> = MarkerDecoder::new().feed(input);
+
+        let result: Vec<MarkerEvent> =
+            MarkerEvents::with_chunk_size(input, MarkerDecoder::new(), 5)
+                .collect::<io::Result<_>>()
+                .unwrap();
+
+        assert_eq!(result, expected);
+        assert_eq!(result[0].end(), 5);
+    }
+
+    #[test]
+    fn test_start_of_packet_marker_index_property() {
+        let alphabet: Vec<char> = ('a'..='z').collect();
+        let mut random = Random::new(2022);
+
+        for seed in 0..500 {
+            let offset = random.next_in_range(0, 200);
+            let length = offset + START_OF_PACKET_MARKER_SIZE + random.next_below(50);
+
+            let stream = DataStreamEncoder::new(&alphabet, seed)
+                .encode(START_OF_PACKET_MARKER_SIZE, offset, length)
+                .unwrap();
+
+            let result = start_of_packet_marker_index(&stream, 0);
+
+            assert_eq!(result, Some(offset), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_start_of_message_marker_index_property() {
+        let alphabet: Vec<char> = ('a'..='z').collect();
+        let mut random = Random::new(2022);
+
+        for seed in 0..500 {
+            let offset = random.next_in_range(0, 200);
+            let length = offset + START_OF_MESSAGE_MARKER_SIZE + random.next_below(50);
+
+            let stream = DataStreamEncoder::new(&alphabet[..random.next_in_range(14, 26)], seed)
+                .encode(START_OF_MESSAGE_MARKER_SIZE, offset, length)
+                .unwrap();
+
+            let result = start_of_message_marker_index(&stream, 0);
+
+            assert_eq!(result, Some(offset), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_marker_decoder_property() {
+        let alphabet: Vec<char> = ('a'..='z').collect();
+        let mut random = Random::new(6);
+
+        for seed in 0..200 {
+            let offset = random.next_in_range(0, 100);
+
+            let stream: String = DataStreamEncoder::new(&alphabet, seed)
+                .encode(START_OF_MESSAGE_MARKER_SIZE, offset, offset + 30)
+                .unwrap()
+                .into_iter()
+                .collect();
+
+            let result = MarkerDecoder::new()
+                .feed(stream.as_bytes())
+                .into_iter()
+                .find(|event| event.kind == MarkerKind::StartOfMessage)
+                .map(|event| event.index);
+
+            assert_eq!(result, Some(offset), "seed {}", seed);
+        }
+    }
 }
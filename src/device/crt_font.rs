@@ -0,0 +1,147 @@
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+pub const GLYPH_SPACING: usize = 1;
+
+const LIT_PIXEL: char = '#';
+
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT]); 16] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+pub fn read_letters(display: &[Vec<char>]) -> Result<String, String> {
+    if display.len() != GLYPH_HEIGHT {
+        return Err(format!(
+            "Display has {} rows, but glyphs are {} rows tall",
+            display.len(),
+            GLYPH_HEIGHT
+        ));
+    }
+
+    let width = display.iter().map(|row| row.len()).min().unwrap_or(0);
+
+    (0..width)
+        .step_by(GLYPH_WIDTH + GLYPH_SPACING)
+        .enumerate()
+        .map(|(glyph_index, col)| read_glyph(display, col, glyph_index))
+        .collect()
+}
+
+fn read_glyph(display: &[Vec<char>], col: usize, glyph_index: usize) -> Result<char, String> {
+    let pixels: Vec<String> = display
+        .iter()
+        .map(|row| {
+            row.iter()
+                .skip(col)
+                .take(GLYPH_WIDTH)
+                .map(|&c| if c == LIT_PIXEL { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+
+    GLYPHS
+        .iter()
+        .find(|(_, glyph)| glyph.iter().zip(pixels.iter()).all(|(l, r)| *l == r))
+        .map(|(letter, _)| *letter)
+        .ok_or_else(|| {
+            format!(
+                "Unrecognized glyph at index {} (column {}): {}",
+                glyph_index,
+                col,
+                pixels.join("/")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display_from(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_read_letters() {
+        let display = display_from(&[
+            "####..##..#....#..#.###..#....####...##.",
+            "#....#..#.#....#..#.#..#.#....#.......#.",
+            "###..#....#....####.###..#....###.....#.",
+            "#....#.##.#....#..#.#..#.#....#.......#.",
+            "#....#..#.#....#..#.#..#.#....#....#..#.",
+            "####..###.####.#..#.###..####.#.....##..",
+        ]);
+
+        let expected = Ok(String::from("EGLHBLFJ"));
+
+        let result = read_letters(&display);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_read_letters_all_glyphs() {
+        let rows: Vec<String> = (0..GLYPH_HEIGHT)
+            .map(|row| {
+                GLYPHS
+                    .iter()
+                    .map(|(_, glyph)| format!("{}.", glyph[row]))
+                    .collect()
+            })
+            .collect();
+        let display: Vec<Vec<char>> = rows.iter().map(|row| row.chars().collect()).collect();
+
+        let expected = Ok(String::from("ABCEFGHJKLOPRSUZ"));
+
+        let result = read_letters(&display);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_read_letters_unrecognized_glyph() {
+        let display = display_from(&[
+            ".##..##..",
+            "#..##..#.",
+            "#..##..#.",
+            "#####..#.",
+            "#..##..#.",
+            "#..#.##..",
+        ]);
+
+        let expected = Err(String::from(
+            "Unrecognized glyph at index 1 (column 5): ##../..#./..#./..#./..#./##..",
+        ));
+
+        let result = read_letters(&display);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_read_letters_wrong_height() {
+        let display = display_from(&["####", "#...", "###."]);
+
+        let expected = Err(String::from(
+            "Display has 3 rows, but glyphs are 6 rows tall",
+        ));
+
+        let result = read_letters(&display);
+
+        assert_eq!(result, expected);
+    }
+}
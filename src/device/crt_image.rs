@@ -0,0 +1,209 @@
+use crate::util::image::{colors_to_png, colors_to_ppm, image_size, ImageFormat, Rgb};
+
+const LIT_PIXEL: char = '#';
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ImageOptions {
+    pub scale: usize,
+    pub lit_color: Rgb,
+    pub dark_color: Rgb,
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            scale: 1,
+            lit_color: Rgb::new(255, 255, 255),
+            dark_color: Rgb::new(0, 0, 0),
+        }
+    }
+}
+
+pub fn export(display: &[Vec<char>], format: ImageFormat, options: &ImageOptions) -> Vec<u8> {
+    match format {
+        ImageFormat::Ppm => to_ppm(display, options),
+        ImageFormat::Png => to_png(display, options),
+        ImageFormat::Svg => to_svg(display, options).into_bytes(),
+    }
+}
+
+pub fn to_ppm(display: &[Vec<char>], options: &ImageOptions) -> Vec<u8> {
+    colors_to_ppm(&colors_of(display, options), options.scale)
+}
+
+pub fn to_png(display: &[Vec<char>], options: &ImageOptions) -> Vec<u8> {
+    colors_to_png(&colors_of(display, options), options.scale)
+}
+
+pub fn to_svg(display: &[Vec<char>], options: &ImageOptions) -> String {
+    let (width, height) = image_size(display, options.scale);
+    let scale = options.scale;
+
+    let mut result = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+
+    result.push_str(&format!(
+        "  <rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        options.dark_color.hex()
+    ));
+
+    for (row_index, row) in display.iter().enumerate() {
+        for (col_index, &pixel) in row.iter().enumerate() {
+            if pixel == LIT_PIXEL {
+                result.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    col_index * scale,
+                    row_index * scale,
+                    scale,
+                    scale,
+                    options.lit_color.hex()
+                ));
+            }
+        }
+    }
+
+    result.push_str("</svg>\n");
+
+    result
+}
+
+// Short rows are padded with dark pixels.
+fn colors_of(display: &[Vec<char>], options: &ImageOptions) -> Vec<Vec<Rgb>> {
+    let width = display.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    display
+        .iter()
+        .map(|row| {
+            (0..width)
+                .map(|col| match row.get(col) {
+                    Some(&LIT_PIXEL) => options.lit_color,
+                    _ => options.dark_color,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::image::{crc32, export_colors, PNG_SIGNATURE};
+
+    fn display_from(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let display = display_from(&["#.", ".#"]);
+        let options = ImageOptions::default();
+
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([255, 255, 255, 0, 0, 0, 0, 0, 0, 255, 255, 255]);
+
+        let result = to_ppm(&display, &options);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_to_ppm_scaled_and_colored() {
+        let display = display_from(&["#."]);
+        let options = ImageOptions {
+            scale: 2,
+            lit_color: Rgb::new(1, 2, 3),
+            dark_color: Rgb::new(4, 5, 6),
+        };
+
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        expected.extend([1, 2, 3, 1, 2, 3, 4, 5, 6, 4, 5, 6]);
+        expected.extend([1, 2, 3, 1, 2, 3, 4, 5, 6, 4, 5, 6]);
+
+        let result = to_ppm(&display, &options);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_to_png() {
+        let display = display_from(&["#.", ".#"]);
+        let options = ImageOptions::default();
+
+        let result = to_png(&display, &options);
+
+        let ihdr_crc = crc32(&result[12..29]);
+
+        assert_eq!(result[..8], PNG_SIGNATURE);
+        assert_eq!(result[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(result[16..24], [0, 0, 0, 2, 0, 0, 0, 2]);
+        assert_eq!(result[29..33], ihdr_crc.to_be_bytes());
+        assert_eq!(
+            result[(result.len() - 12)..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let display = display_from(&["#.", ".."]);
+        let options = ImageOptions {
+            scale: 10,
+            lit_color: Rgb::new(255, 0, 0),
+            dark_color: Rgb::new(0, 0, 0),
+        };
+
+        let expected = String::from(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"20\" viewBox=\"0 0 20 20\">\n\
+            \x20 <rect width=\"20\" height=\"20\" fill=\"#000000\"/>\n\
+            \x20 <rect x=\"0\" y=\"0\" width=\"10\" height=\"10\" fill=\"#ff0000\"/>\n\
+            </svg>\n",
+        );
+
+        let result = to_svg(&display, &options);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_export() {
+        let display = display_from(&["#."]);
+        let options = ImageOptions::default();
+
+        assert_eq!(
+            export(&display, ImageFormat::Ppm, &options),
+            to_ppm(&display, &options)
+        );
+        assert_eq!(
+            export(&display, ImageFormat::Png, &options),
+            to_png(&display, &options)
+        );
+        assert_eq!(
+            export(&display, ImageFormat::Svg, &options),
+            to_svg(&display, &options).into_bytes()
+        );
+    }
+
+    #[test]
+    fn test_export_colors_matches_display() {
+        let display = display_from(&["#.", "."]);
+        let options = ImageOptions::default();
+
+        let white = options.lit_color;
+        let black = options.dark_color;
+
+        let expected = to_ppm(&display, &options);
+
+        let result = export_colors(
+            &[vec![white, black], vec![black, black]],
+            ImageFormat::Ppm,
+            1,
+        );
+
+        assert_eq!(result, expected);
+    }
+}
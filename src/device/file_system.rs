@@ -136,6 +136,35 @@ impl FileSystem {
 
         sum
     }
+
+    pub fn number_of_directories(&self) -> usize {
+        self.all_directories().len()
+    }
+
+    pub fn number_of_files(&self) -> usize {
+        self.all_directories()
+            .iter()
+            .map(|directory| directory.borrow().files.len())
+            .sum()
+    }
+
+    fn all_directories(&self) -> Vec<Rc<RefCell<Directory>>> {
+        let mut result = vec![];
+
+        let mut path: Vec<Rc<RefCell<Directory>>> = vec![Rc::clone(&self.root_directory)];
+
+        while let Some(current_directory) = path.pop() {
+            current_directory
+                .borrow()
+                .directories
+                .values()
+                .for_each(|rc| path.push(Rc::clone(rc)));
+
+            result.push(current_directory);
+        }
+
+        result
+    }
 }
 
 impl FileSystem {
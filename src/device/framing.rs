@@ -0,0 +1,256 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+
+use super::communication_system::{
+    MarkerDecoder, MarkerKind, START_OF_MESSAGE_MARKER_SIZE, START_OF_PACKET_MARKER_SIZE,
+};
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct FramingConfig {
+    pub packet_marker_size: usize,
+    pub message_marker_size: usize,
+}
+
+impl FramingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        MarkerDecoder::with_marker_sizes(self.packet_marker_size, self.message_marker_size)
+            .map(|_| ())
+    }
+}
+
+impl Default for FramingConfig {
+    fn default() -> FramingConfig {
+        FramingConfig {
+            packet_marker_size: START_OF_PACKET_MARKER_SIZE,
+            message_marker_size: START_OF_MESSAGE_MARKER_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Frame {
+    pub kind: MarkerKind,
+    pub marker_index: usize,
+    pub payload_offset: usize,
+    pub payload: Vec<u8>,
+}
+
+/*
+A frame starts right after its marker and runs until the next marker of the same
+kind starts, or until the stream ends. Packets and messages are framed
+independently, so a message frame will usually contain several packet markers.
+*/
+#[derive(Debug, PartialEq)]
+pub struct FrameDecoder {
+    markers: MarkerDecoder,
+    open_frames: Vec<Frame>,
+}
+
+impl FrameDecoder {
+    pub fn new(config: FramingConfig) -> Result<FrameDecoder, String> {
+        Ok(FrameDecoder {
+            markers: MarkerDecoder::with_marker_sizes(
+                config.packet_marker_size,
+                config.message_marker_size,
+            )?,
+            open_frames: vec![],
+        })
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Frame> {
+        let mut result = vec![];
+
+        for &byte in chunk {
+            self.open_frames
+                .iter_mut()
+                .for_each(|frame| frame.payload.push(byte));
+
+            for event in self.markers.feed(&[byte]) {
+                if let Some(position) = self
+                    .open_frames
+                    .iter()
+                    .position(|frame| frame.kind == event.kind)
+                {
+                    let mut frame = self.open_frames.remove(position);
+
+                    frame
+                        .payload
+                        .truncate(frame.payload.len().saturating_sub(event.size));
+
+                    result.push(frame);
+                }
+
+                self.open_frames.push(Frame {
+                    kind: event.kind,
+                    marker_index: event.index,
+                    payload_offset: event.end(),
+                    payload: vec![],
+                });
+            }
+        }
+
+        result
+    }
+
+    pub fn finish(&mut self) -> Vec<Frame> {
+        let mut result: Vec<Frame> = self.open_frames.drain(..).collect();
+
+        result.sort_by_key(|frame| frame.payload_offset);
+
+        result
+    }
+}
+
+#[derive(Debug)]
+pub struct Frames<R: Read> {
+    reader: R,
+    decoder: FrameDecoder,
+    chunk: Vec<u8>,
+    pending_frames: VecDeque<Frame>,
+    reached_end: bool,
+}
+
+impl<R: Read> Frames<R> {
+    pub fn new(reader: R, config: FramingConfig) -> Result<Frames<R>, String> {
+        Ok(Frames {
+            reader,
+            decoder: FrameDecoder::new(config)?,
+            chunk: vec![0; DEFAULT_CHUNK_SIZE],
+            pending_frames: VecDeque::new(),
+            reached_end: false,
+        })
+    }
+}
+
+impl<R: Read> Iterator for Frames<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending_frames.is_empty() && !self.reached_end {
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => {
+                    self.reached_end = true;
+
+                    self.pending_frames.extend(self.decoder.finish());
+                }
+                Ok(bytes_read) => {
+                    let frames = self.decoder.feed(&self.chunk[..bytes_read]);
+
+                    self.pending_frames.extend(frames);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => (),
+                Err(error) => {
+                    self.reached_end = true;
+
+                    return Some(Err(error));
+                }
+            }
+        }
+
+        self.pending_frames.pop_front().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: MarkerKind, marker_index: usize, payload_offset: usize, payload: &str) -> Frame {
+        Frame {
+            kind,
+            marker_index,
+            payload_offset,
+            payload: payload.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_frame_decoder() {
+        let config = FramingConfig {
+            packet_marker_size: 2,
+            message_marker_size: 4,
+        };
+        let mut decoder = FrameDecoder::new(config).unwrap();
+
+        let expected = vec![
+            frame(MarkerKind::StartOfPacket, 1, 3, "a"),
+            frame(MarkerKind::StartOfPacket, 4, 6, ""),
+            frame(MarkerKind::StartOfPacket, 6, 8, "x"),
+            frame(MarkerKind::StartOfMessage, 4, 8, "xxyzz"),
+            frame(MarkerKind::StartOfPacket, 9, 11, "zz"),
+        ];
+
+        let mut result = decoder.feed(b"aabaacdxxxyzz");
+        result.extend(decoder.finish());
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_frame_decoder_default_config() {
+        let mut decoder = FrameDecoder::new(FramingConfig::default()).unwrap();
+
+        let result = decoder.feed(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb");
+
+        assert_eq!(result[0], frame(MarkerKind::StartOfPacket, 3, 7, ""));
+        assert_eq!(result[1], frame(MarkerKind::StartOfPacket, 7, 11, ""));
+    }
+
+    #[test]
+    fn test_frames_from_reader() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+
+        let mut decoder = FrameDecoder::new(FramingConfig::default()).unwrap();
+
+        let mut expected = decoder.feed(input.as_bytes());
+        expected.extend(decoder.finish());
+
+        let result: Vec<Frame> = Frames::new(input.as_bytes(), FramingConfig::default())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_frame_decoder_non_ascii_payload() {
+        let config = FramingConfig {
+            packet_marker_size: 2,
+            message_marker_size: 4,
+        };
+        let mut decoder = FrameDecoder::new(config).unwrap();
+
+        let expected = vec![
+            Frame {
+                kind: MarkerKind::StartOfPacket,
+                marker_index: 0,
+                payload_offset: 2,
+                payload: vec![0xC3],
+            },
+            frame(MarkerKind::StartOfPacket, 3, 5, ""),
+        ];
+
+        let mut result = decoder.feed(b"ab\xC3\xC3x");
+        result.extend(decoder.finish());
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_zero_marker_size() {
+        let config = FramingConfig {
+            packet_marker_size: 0,
+            message_marker_size: 4,
+        };
+
+        let expected = Err(String::from("Marker sizes must be at least 1"));
+
+        let result = FrameDecoder::new(config).map(|_| ());
+
+        assert_eq!(result, expected);
+        assert_eq!(config.validate(), expected);
+    }
+}
@@ -1,20 +1,41 @@
+mod builder;
+mod byte_codec;
 mod commands;
 mod communication_system;
+mod crt_font;
+mod crt_image;
 mod file_system;
+mod framing;
+mod peripheral;
+mod status;
+mod stream_encoder;
 mod video_system;
 
 use std::collections::HashSet;
+use std::io::Read;
+
+use crate::util::image::ImageFormat;
 
 use commands::ExecutedCommand;
 use communication_system::{START_OF_MESSAGE_MARKER_SIZE, START_OF_PACKET_MARKER_SIZE};
 use file_system::FileSystem;
-use video_system::VideoSystem;
+use video_system::{CpuInstruction, VideoSystem};
+
+pub use builder::DeviceBuilder;
+pub use communication_system::{MarkerDecoder, MarkerEvent, MarkerEvents, MarkerKind};
+pub use crt_image::ImageOptions;
+pub use framing::{Frame, FrameDecoder, Frames, FramingConfig};
+pub use peripheral::{HandlerAddress, InterruptLine, Peripheral, Timer};
+pub use status::DeviceStatus;
+pub use stream_encoder::DataStreamEncoder;
+pub use video_system::{Cycle, CycleState};
 
 const TOTAL_FILE_SYSTEM_SIZE: u64 = 70_000_000;
 
 #[derive(Debug, PartialEq)]
 pub struct Device {
     data_stream_buffer: Vec<char>,
+    framing_config: FramingConfig,
     file_system: FileSystem,
     video_system: VideoSystem,
 }
@@ -23,11 +44,43 @@ impl Device {
     pub fn new() -> Device {
         Device {
             data_stream_buffer: vec![],
+            framing_config: FramingConfig::default(),
             file_system: FileSystem::new(),
             video_system: VideoSystem::new(),
         }
     }
 
+    pub fn from_bundle(input: &[String]) -> Result<Device, String> {
+        DeviceBuilder::from_bundle(input).and_then(DeviceBuilder::build)
+    }
+
+    pub fn status_report(&self) -> DeviceStatus {
+        let used_disk_space = self.file_system.get_size();
+
+        DeviceStatus {
+            data_stream_length: self.data_stream_buffer.len(),
+            start_of_packet: self.number_of_characters_before_first_start_of_packet(),
+            start_of_message: self.number_of_characters_before_first_start_of_message(),
+            received_messages: self.received_messages().len(),
+            used_disk_space,
+            available_disk_space: TOTAL_FILE_SYSTEM_SIZE - used_disk_space,
+            directories: self.file_system.number_of_directories(),
+            files: self.file_system.number_of_files(),
+            video_tick: self.video_system.current_tick(),
+            queued_instructions: self.video_system.number_of_queued_cpu_instructions(),
+            x_register: self.video_system.get_cpu_register_value('X'),
+            display_letters: self.video_system.read_display().ok(),
+        }
+    }
+
+    pub fn set_framing_config(&mut self, config: FramingConfig) -> Result<(), String> {
+        config.validate()?;
+
+        self.framing_config = config;
+
+        Ok(())
+    }
+
     pub fn number_of_characters_before_first_start_of_packet(&self) -> Option<usize> {
         communication_system::start_of_packet_marker_index(&self.data_stream_buffer, 0)
             .map(|index| index + START_OF_PACKET_MARKER_SIZE)
@@ -38,6 +91,38 @@ impl Device {
             .map(|index| index + START_OF_MESSAGE_MARKER_SIZE)
     }
 
+    /*
+    Frames are found in the UTF-8 encoding of the buffer, like any other byte stream,
+    so `marker_index` and `payload_offset` count bytes rather than characters. The two
+    only agree while the stream is ASCII.
+    */
+    pub fn received_frames(&self) -> Vec<Frame> {
+        let data: String = self.data_stream_buffer.iter().collect();
+        let mut decoder =
+            FrameDecoder::new(self.framing_config).expect("The framing config was validated");
+
+        let mut frames = decoder.feed(data.as_bytes());
+        frames.extend(decoder.finish());
+
+        frames
+    }
+
+    pub fn received_messages(&self) -> Vec<String> {
+        self.received_frames()
+            .into_iter()
+            .filter(|frame| frame.kind == MarkerKind::StartOfMessage)
+            .map(|frame| String::from_utf8_lossy(&frame.payload).into_owned())
+            .collect()
+    }
+
+    pub fn stream_frames<R: Read>(reader: R, config: FramingConfig) -> Result<Frames<R>, String> {
+        Frames::new(reader, config)
+    }
+
+    pub fn stream_markers<R: Read>(reader: R) -> MarkerEvents<R> {
+        MarkerEvents::new(reader, MarkerDecoder::new())
+    }
+
     pub fn populate_file_system_from_executed_commands(&mut self, commands: &[String]) {
         self.file_system =
             FileSystem::create_from_executed_commands(&ExecutedCommand::extract_commands(commands));
@@ -69,6 +154,54 @@ impl Device {
         self.video_system.add_cpu_instructions(input);
     }
 
+    pub fn encode_video_system_cpu_instructions(input: &[String]) -> Vec<u8> {
+        let instructions: Vec<CpuInstruction> = input.iter().map(CpuInstruction::from).collect();
+
+        CpuInstruction::encode_stream(&instructions)
+    }
+
+    pub fn add_encoded_video_system_cpu_instructions(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<(), String> {
+        self.video_system
+            .add_decoded_cpu_instructions(&CpuInstruction::decode_stream(bytes)?);
+
+        Ok(())
+    }
+
+    pub fn attach_video_system_peripheral(&mut self, peripheral: Box<dyn Peripheral>) {
+        self.video_system.attach_peripheral(peripheral);
+    }
+
+    pub fn install_video_system_interrupt_handler(
+        &mut self,
+        line: InterruptLine,
+        input: &[String],
+    ) -> HandlerAddress {
+        let address = self.video_system.install_interrupt_handler(input);
+
+        self.video_system
+            .set_interrupt_vector(line, address)
+            .expect("Handler was just installed");
+
+        address
+    }
+
+    pub fn run_video_system_until_tick(&mut self, tick: usize) {
+        self.video_system.run_until_tick(tick);
+    }
+
+    pub fn snapshot_video_system(&self) -> Result<Vec<u8>, String> {
+        self.video_system.snapshot()
+    }
+
+    pub fn restore_video_system(&mut self, snapshot: &[u8]) -> Result<(), String> {
+        self.video_system = VideoSystem::restore(snapshot)?;
+
+        Ok(())
+    }
+
     pub fn get_video_system_x_signal_strengths_at(&mut self, ticks: &HashSet<usize>) -> Vec<i32> {
         self.video_system
             .get_cpu_register_signal_strengths_at('X', ticks)
@@ -81,6 +214,30 @@ impl Device {
         self.video_system.get_display()
     }
 
+    pub fn read_video_system_display(&self) -> Result<String, String> {
+        self.video_system.read_display()
+    }
+
+    pub fn export_video_system_display(
+        &self,
+        format: ImageFormat,
+        options: &ImageOptions,
+    ) -> Vec<u8> {
+        crt_image::export(&self.video_system.get_display(), format, options)
+    }
+
+    pub fn export_video_system_frames(
+        &self,
+        format: ImageFormat,
+        options: &ImageOptions,
+    ) -> Vec<Vec<u8>> {
+        self.video_system
+            .get_frames()
+            .iter()
+            .map(|frame| crt_image::export(frame, format, options))
+            .collect()
+    }
+
     pub fn print_video_system_display(&self) {
         self.video_system.print_display();
     }
@@ -90,6 +247,7 @@ impl From<&String> for Device {
     fn from(input: &String) -> Device {
         Device {
             data_stream_buffer: input.chars().collect(),
+            framing_config: FramingConfig::default(),
             file_system: FileSystem::new(),
             video_system: VideoSystem::new(),
         }
@@ -100,6 +258,8 @@ impl From<&String> for Device {
 mod tests {
     use super::*;
 
+    use crate::util::file_reader::to_string_vector;
+
     #[test]
     fn test_number_of_characters_before_first_start_of_packet() {
         let input = [
@@ -149,4 +309,166 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_add_encoded_video_system_cpu_instructions() {
+        let input = to_string_vector("test_inputs/day_10_part_1.txt").unwrap();
+
+        let mut device = Device::new();
+        let mut encoded_device = Device::new();
+
+        device.add_video_system_cpu_instructions(&input);
+        encoded_device
+            .add_encoded_video_system_cpu_instructions(
+                &Device::encode_video_system_cpu_instructions(&input),
+            )
+            .unwrap();
+
+        let ticks = HashSet::from([20, 60, 100, 140, 180, 220]);
+
+        let expected = device.get_video_system_x_signal_strengths_at(&ticks);
+
+        let result = encoded_device.get_video_system_x_signal_strengths_at(&ticks);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_restore_video_system() {
+        let input = to_string_vector("test_inputs/day_10_part_1.txt").unwrap();
+
+        let mut device = Device::new();
+
+        device.add_video_system_cpu_instructions(&input);
+        device.run_video_system_until_tick(120);
+
+        let snapshot = device.snapshot_video_system().unwrap();
+
+        let mut restored_device = Device::new();
+
+        restored_device.restore_video_system(&snapshot).unwrap();
+
+        assert_eq!(restored_device, device);
+    }
+
+    #[test]
+    fn test_stream_markers() {
+        let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
+
+        let expected = vec![
+            Device::from(&String::from(input)).number_of_characters_before_first_start_of_packet(),
+            Device::from(&String::from(input)).number_of_characters_before_first_start_of_message(),
+        ];
+
+        let events: Vec<MarkerEvent> = Device::stream_markers(input.as_bytes())
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+
+        let result: Vec<Option<usize>> = [MarkerKind::StartOfPacket, MarkerKind::StartOfMessage]
+            .iter()
+            .map(|kind| {
+                events
+                    .iter()
+                    .find(|event| event.kind == *kind)
+                    .map(|event| event.end())
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_status_report() {
+        let input = [
+            "[data_stream]",
+            "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+            "[file_system]",
+            "$ cd /",
+            "$ ls",
+            "dir a",
+            "100 b.txt",
+            "$ cd a",
+            "$ ls",
+            "20 c",
+            "3 d",
+            "[video_program]",
+            "noop",
+            "addx 3",
+            "addx -5",
+        ]
+        .map(String::from);
+
+        let mut device = Device::from_bundle(&input).unwrap();
+
+        device.run_video_system_until_tick(3);
+
+        let expected = DeviceStatus {
+            data_stream_length: 30,
+            start_of_packet: Some(7),
+            start_of_message: Some(19),
+            received_messages: 1,
+            used_disk_space: 123,
+            available_disk_space: 69_999_877,
+            directories: 2,
+            files: 3,
+            video_tick: 4,
+            queued_instructions: 1,
+            x_register: Some(4),
+            display_letters: None,
+        };
+
+        let result = device.status_report();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_received_messages() {
+        let mut device = Device::from(&String::from("abcdzzzzzyxwqqqq"));
+
+        device
+            .set_framing_config(FramingConfig {
+                packet_marker_size: 2,
+                message_marker_size: 4,
+            })
+            .unwrap();
+
+        let expected = vec![String::from("zzzz"), String::from("qqqq")];
+
+        let result = device.received_messages();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_received_frames_count_bytes() {
+        let mut device = Device::from(&String::from("xéabab"));
+
+        device
+            .set_framing_config(FramingConfig {
+                packet_marker_size: 3,
+                message_marker_size: 4,
+            })
+            .unwrap();
+
+        // `é` is two bytes, so the message marker `xéa` ends at byte 4 rather than 3.
+        let expected = vec![
+            Frame {
+                kind: MarkerKind::StartOfPacket,
+                marker_index: 0,
+                payload_offset: 3,
+                payload: b"abab".to_vec(),
+            },
+            Frame {
+                kind: MarkerKind::StartOfMessage,
+                marker_index: 0,
+                payload_offset: 4,
+                payload: b"bab".to_vec(),
+            },
+        ];
+
+        let result = device.received_frames();
+
+        assert_eq!(result, expected);
+    }
 }
@@ -0,0 +1,197 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use super::video_system::{Cycle, CycleState};
+
+pub type InterruptLine = u8;
+pub type HandlerAddress = usize;
+
+pub trait Peripheral: Debug + PeripheralEq {
+    fn on_cycle(&mut self, cycle: &Cycle) -> Option<InterruptLine>;
+}
+
+// Lets two boxed peripherals compare equal when they are the same type with equal state.
+pub trait PeripheralEq {
+    fn as_any(&self) -> &dyn Any;
+
+    fn eq_peripheral(&self, other: &dyn Peripheral) -> bool;
+}
+
+impl<T: Peripheral + PartialEq + 'static> PeripheralEq for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_peripheral(&self, other: &dyn Peripheral) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Timer {
+    line: InterruptLine,
+    period: usize,
+}
+
+impl Timer {
+    pub fn new(line: InterruptLine, period: usize) -> Timer {
+        Timer { line, period }
+    }
+}
+
+impl Peripheral for Timer {
+    fn on_cycle(&mut self, cycle: &Cycle) -> Option<InterruptLine> {
+        let period_elapsed = (self.period != 0) && cycle.tick().is_multiple_of(self.period);
+
+        if period_elapsed && (cycle.state() == CycleState::Ending) {
+            Some(self.line)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PeripheralBus {
+    peripherals: Vec<Box<dyn Peripheral>>,
+    interrupt_vectors: BTreeMap<InterruptLine, HandlerAddress>,
+}
+
+impl PeripheralBus {
+    pub fn new() -> PeripheralBus {
+        PeripheralBus {
+            peripherals: vec![],
+            interrupt_vectors: BTreeMap::new(),
+        }
+    }
+
+    pub fn attach(&mut self, peripheral: Box<dyn Peripheral>) {
+        self.peripherals.push(peripheral);
+    }
+
+    pub fn set_interrupt_vector(&mut self, line: InterruptLine, address: HandlerAddress) {
+        self.interrupt_vectors.insert(line, address);
+    }
+
+    pub fn has_peripherals(&self) -> bool {
+        !self.peripherals.is_empty()
+    }
+
+    pub fn interrupt_vectors(&self) -> &BTreeMap<InterruptLine, HandlerAddress> {
+        &self.interrupt_vectors
+    }
+
+    /*
+    Every peripheral sees every phase, in the order they were attached. Lines
+    without a vector are dropped, just like an unmasked line with no handler.
+    */
+    pub fn broadcast(&mut self, cycle: &Cycle) -> Vec<HandlerAddress> {
+        let raised_lines: Vec<InterruptLine> = self
+            .peripherals
+            .iter_mut()
+            .filter_map(|peripheral| peripheral.on_cycle(cycle))
+            .collect();
+
+        raised_lines
+            .into_iter()
+            .filter_map(|line| self.interrupt_vectors.get(&line).cloned())
+            .collect()
+    }
+}
+
+impl PartialEq for PeripheralBus {
+    fn eq(&self, other: &PeripheralBus) -> bool {
+        (self.interrupt_vectors == other.interrupt_vectors)
+            && (self.peripherals.len() == other.peripherals.len())
+            && self
+                .peripherals
+                .iter()
+                .zip(&other.peripherals)
+                .all(|(peripheral, other)| peripheral.eq_peripheral(other.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::device::video_system::ClockCircuit;
+
+    #[derive(Debug, PartialEq)]
+    struct Button {
+        pressed_at_tick: usize,
+    }
+
+    impl Peripheral for Button {
+        fn on_cycle(&mut self, cycle: &Cycle) -> Option<InterruptLine> {
+            if (cycle.tick() == self.pressed_at_tick) && (cycle.state() == CycleState::Starting) {
+                Some(7)
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_timer_on_cycle() {
+        let mut timer = Timer::new(1, 2);
+        let clock = ClockCircuit::new();
+
+        let expected = vec![(2, 1), (4, 1)];
+
+        let result: Vec<(usize, InterruptLine)> = clock
+            .take(15)
+            .filter_map(|cycle| timer.on_cycle(&cycle).map(|line| (cycle.tick(), line)))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_broadcast() {
+        let mut bus = PeripheralBus::new();
+        let clock = ClockCircuit::new();
+
+        bus.attach(Box::new(Timer::new(1, 3)));
+        bus.attach(Box::new(Button { pressed_at_tick: 2 }));
+        bus.attach(Box::new(Timer::new(2, 1)));
+        bus.set_interrupt_vector(1, 10);
+        bus.set_interrupt_vector(7, 70);
+
+        let expected = vec![(2, 70), (3, 10)];
+
+        let result: Vec<(usize, HandlerAddress)> = clock
+            .take(9)
+            .flat_map(|cycle| {
+                bus.broadcast(&cycle)
+                    .into_iter()
+                    .map(move |address| (cycle.tick(), address))
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_bus_equality() {
+        let mut bus = PeripheralBus::new();
+        let mut other_bus = PeripheralBus::new();
+
+        bus.attach(Box::new(Timer::new(1, 3)));
+        other_bus.attach(Box::new(Button { pressed_at_tick: 3 }));
+
+        let expected = vec![false, true, false];
+
+        let mut result = vec![bus == other_bus];
+
+        other_bus = PeripheralBus::new();
+        other_bus.attach(Box::new(Timer::new(1, 3)));
+        result.push(bus == other_bus);
+
+        other_bus.set_interrupt_vector(1, 10);
+        result.push(bus == other_bus);
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DeviceStatus {
+    pub data_stream_length: usize,
+    pub start_of_packet: Option<usize>,
+    pub start_of_message: Option<usize>,
+    pub received_messages: usize,
+    pub used_disk_space: u64,
+    pub available_disk_space: u64,
+    pub directories: usize,
+    pub files: usize,
+    pub video_tick: usize,
+    pub queued_instructions: usize,
+    pub x_register: Option<i32>,
+    pub display_letters: Option<String>,
+}
+
+fn or_none<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or(String::from("none"), |value| value.to_string())
+}
+
+impl fmt::Display for DeviceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Communication: {} characters, start of packet after {}, start of message after {}, {} messages",
+            self.data_stream_length,
+            or_none(&self.start_of_packet),
+            or_none(&self.start_of_message),
+            self.received_messages
+        )?;
+        writeln!(
+            f,
+            "File system: {} used, {} available, {} directories, {} files",
+            self.used_disk_space, self.available_disk_space, self.directories, self.files
+        )?;
+        write!(
+            f,
+            "Video system: tick {}, {} instructions queued, X = {}, display reads {}",
+            self.video_tick,
+            self.queued_instructions,
+            or_none(&self.x_register),
+            or_none(&self.display_letters)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let status = DeviceStatus {
+            data_stream_length: 30,
+            start_of_packet: Some(7),
+            start_of_message: None,
+            received_messages: 0,
+            used_disk_space: 584,
+            available_disk_space: 69_999_416,
+            directories: 1,
+            files: 1,
+            video_tick: 1,
+            queued_instructions: 2,
+            x_register: Some(1),
+            display_letters: None,
+        };
+
+        let expected = "Communication: 30 characters, start of packet after 7, start of message after none, 0 messages\n\
+                        File system: 584 used, 69999416 available, 1 directories, 1 files\n\
+                        Video system: tick 1, 2 instructions queued, X = 1, display reads none";
+
+        let result = status.to_string();
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::util::random::Random;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DataStreamEncoder {
+    alphabet: Vec<char>,
+    random: Random,
+}
+
+impl DataStreamEncoder {
+    pub fn new(alphabet: &[char], seed: u64) -> DataStreamEncoder {
+        let mut seen = HashSet::new();
+
+        DataStreamEncoder {
+            alphabet: alphabet
+                .iter()
+                .cloned()
+                .filter(|c| seen.insert(*c))
+                .collect(),
+            random: Random::new(seed),
+        }
+    }
+
+    /*
+    Every window that starts before the marker has to repeat a character. The body is
+    generated so that any window that would otherwise be unique reuses one of its own
+    characters, and the character right before the marker is a copy of the marker's
+    first character, which covers every window straddling the marker.
+    */
+    pub fn encode(
+        &mut self,
+        marker_size: usize,
+        marker_offset: usize,
+        length: usize,
+    ) -> Result<Vec<char>, String> {
+        if marker_size == 0 {
+            return Err(String::from("Marker size must be at least 1"));
+        }
+
+        if self.alphabet.len() < marker_size {
+            return Err(format!(
+                "Alphabet has {} unique characters, but the marker needs {}",
+                self.alphabet.len(),
+                marker_size
+            ));
+        }
+
+        if length < marker_offset + marker_size {
+            return Err(format!(
+                "Stream of length {} can't fit a marker of size {} at offset {}",
+                length, marker_size, marker_offset
+            ));
+        }
+
+        if (marker_size == 1) && (marker_offset != 0) {
+            return Err(String::from(
+                "A marker of size 1 is always found at offset 0",
+            ));
+        }
+
+        let mut result = vec![];
+
+        while result.len() + 1 < marker_offset {
+            let next_char = self.next_char_repeating_window(&result, marker_size);
+
+            result.push(next_char);
+        }
+
+        let mut marker = vec![];
+
+        if marker_offset != 0 {
+            let first_char = self.next_char_repeating_window(&result, marker_size);
+
+            result.push(first_char);
+            marker.push(first_char);
+        }
+
+        while marker.len() < marker_size {
+            let unused_chars: Vec<char> = self
+                .alphabet
+                .iter()
+                .cloned()
+                .filter(|c| !marker.contains(c))
+                .collect();
+
+            marker.push(*self.random.choose(&unused_chars).unwrap());
+        }
+
+        result.extend(marker);
+
+        while result.len() < length {
+            result.push(*self.random.choose(&self.alphabet).unwrap());
+        }
+
+        Ok(result)
+    }
+
+    fn next_char_repeating_window(&mut self, stream: &[char], marker_size: usize) -> char {
+        let window = &stream[stream.len().saturating_sub(marker_size - 1)..];
+        let window_is_full = window.len() == (marker_size - 1);
+        let window_has_repeat = window.iter().collect::<HashSet<&char>>().len() != window.len();
+
+        if window_is_full && !window_has_repeat {
+            *self.random.choose(window).unwrap()
+        } else {
+            *self.random.choose(&self.alphabet).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+
+    #[test]
+    fn test_encode_same_seed_same_stream() {
+        let alphabet: Vec<char> = ALPHABET.chars().collect();
+
+        let expected = DataStreamEncoder::new(&alphabet, 3).encode(4, 10, 30);
+
+        let result = DataStreamEncoder::new(&alphabet, 3).encode(4, 10, 30);
+
+        assert_eq!(result, expected);
+        assert_eq!(result.unwrap().len(), 30);
+    }
+
+    #[test]
+    fn test_encode_marker_window_is_unique() {
+        let alphabet: Vec<char> = ALPHABET.chars().collect();
+
+        let stream = DataStreamEncoder::new(&alphabet, 11)
+            .encode(14, 20, 40)
+            .unwrap();
+
+        let result: HashSet<char> = stream[20..34].iter().cloned().collect();
+
+        assert_eq!(result.len(), 14);
+    }
+
+    #[test]
+    fn test_encode_alphabet_too_small() {
+        let alphabet: Vec<char> = "abcc".chars().collect();
+
+        let expected = Err(String::from(
+            "Alphabet has 3 unique characters, but the marker needs 4",
+        ));
+
+        let result = DataStreamEncoder::new(&alphabet, 0).encode(4, 0, 10);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_encode_stream_too_short() {
+        let alphabet: Vec<char> = ALPHABET.chars().collect();
+
+        let expected = Err(String::from(
+            "Stream of length 10 can't fit a marker of size 4 at offset 7",
+        ));
+
+        let result = DataStreamEncoder::new(&alphabet, 0).encode(4, 7, 10);
+
+        assert_eq!(result, expected);
+    }
+}
@@ -1,7 +1,21 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
+use std::str::FromStr;
+
+use super::byte_codec::{ByteReader, ByteWriter};
+use super::crt_font;
+use super::peripheral::{HandlerAddress, InterruptLine, Peripheral, PeripheralBus};
+
+const DISPLAY_WIDTH: usize = 40;
+const DISPLAY_HEIGHT: usize = 6;
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"VSS1";
+
+const NO_OP_OPCODE: u8 = 0x00;
+const ADD_OPCODE: u8 = 0x01;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum CycleState {
+pub enum CycleState {
     Starting,
     Executing,
     Ending,
@@ -21,6 +35,14 @@ impl Cycle {
         }
     }
 
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn state(&self) -> CycleState {
+        self.state
+    }
+
     fn next_state(&self) -> Cycle {
         match &self.state {
             CycleState::Starting => Cycle {
@@ -37,6 +59,28 @@ impl Cycle {
             },
         }
     }
+
+    fn encode(&self, writer: &mut ByteWriter) {
+        writer.write_u64(self.tick as u64);
+        writer.write_u8(match self.state {
+            CycleState::Starting => 0,
+            CycleState::Executing => 1,
+            CycleState::Ending => 2,
+        });
+    }
+
+    fn decode(reader: &mut ByteReader) -> Result<Cycle, String> {
+        let tick = reader.read_u64()? as usize;
+
+        let state = match reader.read_u8()? {
+            0 => CycleState::Starting,
+            1 => CycleState::Executing,
+            2 => CycleState::Ending,
+            other => return Err(format!("Unknown cycle state: {}", other)),
+        };
+
+        Ok(Cycle { tick, state })
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -71,12 +115,51 @@ pub enum CpuInstruction {
 }
 
 impl CpuInstruction {
+    pub fn encode_stream(instructions: &[CpuInstruction]) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+
+        instructions
+            .iter()
+            .for_each(|instruction| instruction.encode(&mut writer));
+
+        writer.into_bytes()
+    }
+
+    pub fn decode_stream(bytes: &[u8]) -> Result<Vec<CpuInstruction>, String> {
+        let mut reader = ByteReader::new(bytes);
+        let mut result = vec![];
+
+        while !reader.is_empty() {
+            result.push(CpuInstruction::decode(&mut reader)?);
+        }
+
+        Ok(result)
+    }
+
     fn number_of_cycles_to_complete(&self) -> usize {
         match &self {
             Self::NoOp => 1,
             Self::Add(_) => 2,
         }
     }
+
+    fn encode(&self, writer: &mut ByteWriter) {
+        match self {
+            CpuInstruction::NoOp => writer.write_u8(NO_OP_OPCODE),
+            CpuInstruction::Add(value) => {
+                writer.write_u8(ADD_OPCODE);
+                writer.write_i32(*value);
+            }
+        }
+    }
+
+    fn decode(reader: &mut ByteReader) -> Result<CpuInstruction, String> {
+        match reader.read_u8()? {
+            NO_OP_OPCODE => Ok(CpuInstruction::NoOp),
+            ADD_OPCODE => reader.read_i32().map(CpuInstruction::Add),
+            other => Err(format!("Unknown opcode: {:#04x}", other)),
+        }
+    }
 }
 
 impl From<&String> for CpuInstruction {
@@ -93,12 +176,29 @@ impl From<&String> for CpuInstruction {
     }
 }
 
+impl FromStr for CpuInstruction {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<CpuInstruction, String> {
+        match input.split_once(' ') {
+            None if input == "noop" => Ok(CpuInstruction::NoOp),
+            Some(("addx", value)) => value
+                .parse()
+                .map(CpuInstruction::Add)
+                .map_err(|_| format!("Bad addx instruction: {}", input)),
+            _ => Err(format!("Unknown instruction: {}", input)),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CPU {
     registers: HashMap<char, i32>,
     instructions: VecDeque<CpuInstruction>,
     current_instruction: Option<CpuInstruction>,
     ticks_left_for_current_instruction: usize,
+    interrupt_handlers: Vec<Vec<CpuInstruction>>,
+    pending_interrupts: VecDeque<HandlerAddress>,
 }
 
 impl CPU {
@@ -108,13 +208,35 @@ impl CPU {
             instructions: VecDeque::new(),
             current_instruction: None,
             ticks_left_for_current_instruction: 0,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         }
     }
 
+    pub fn install_interrupt_handler(&mut self, handler: &[CpuInstruction]) -> HandlerAddress {
+        self.interrupt_handlers.push(handler.to_vec());
+
+        self.interrupt_handlers.len() - 1
+    }
+
+    pub fn raise_interrupt(&mut self, address: HandlerAddress) -> Result<(), String> {
+        if self.interrupt_handlers.len() <= address {
+            return Err(format!("No interrupt handler at address {}", address));
+        }
+
+        self.pending_interrupts.push_back(address);
+
+        Ok(())
+    }
+
     pub fn get_register_value(&self, register: char) -> Option<i32> {
         self.registers.get(&register).cloned()
     }
 
+    fn number_of_queued_instructions(&self) -> usize {
+        self.instructions.len() + usize::from(self.current_instruction.is_some())
+    }
+
     fn add_instructions(&mut self, instructions: &[CpuInstruction]) {
         self.instructions.extend(instructions.iter());
     }
@@ -123,6 +245,10 @@ impl CPU {
         match &cycle.state {
             CycleState::Starting => {
                 if self.current_instruction.is_none() {
+                    if let Some(address) = self.pending_interrupts.pop_front() {
+                        self.enter_interrupt_handler(address);
+                    }
+
                     self.current_instruction = self.instructions.pop_front();
 
                     self.ticks_left_for_current_instruction = self
@@ -157,11 +283,111 @@ impl CPU {
             CpuInstruction::NoOp => (),
         }
     }
+
+    fn enter_interrupt_handler(&mut self, address: HandlerAddress) {
+        self.interrupt_handlers[address]
+            .iter()
+            .rev()
+            .for_each(|&instruction| self.instructions.push_front(instruction));
+    }
+
+    fn is_finished(&self) -> bool {
+        self.instructions.is_empty()
+            && self.current_instruction.is_none()
+            && self.pending_interrupts.is_empty()
+    }
+
+    fn encode(&self, writer: &mut ByteWriter) {
+        let mut registers: Vec<(&char, &i32)> = self.registers.iter().collect();
+
+        registers.sort();
+
+        writer.write_len(registers.len());
+        registers.into_iter().for_each(|(&register, &value)| {
+            writer.write_u32(register as u32);
+            writer.write_i32(value);
+        });
+
+        match &self.current_instruction {
+            Some(instruction) => {
+                writer.write_u8(1);
+                instruction.encode(writer);
+            }
+            None => writer.write_u8(0),
+        }
+
+        writer.write_u64(self.ticks_left_for_current_instruction as u64);
+
+        let instructions: Vec<CpuInstruction> = self.instructions.iter().cloned().collect();
+        let encoded_instructions = CpuInstruction::encode_stream(&instructions);
+
+        writer.write_len(encoded_instructions.len());
+        writer.write_bytes(&encoded_instructions);
+
+        writer.write_len(self.interrupt_handlers.len());
+        self.interrupt_handlers.iter().for_each(|handler| {
+            let encoded_handler = CpuInstruction::encode_stream(handler);
+
+            writer.write_len(encoded_handler.len());
+            writer.write_bytes(&encoded_handler);
+        });
+
+        writer.write_len(self.pending_interrupts.len());
+        self.pending_interrupts
+            .iter()
+            .for_each(|&address| writer.write_u64(address as u64));
+    }
+
+    fn decode(reader: &mut ByteReader) -> Result<CPU, String> {
+        let mut registers = HashMap::new();
+
+        for _ in 0..reader.read_len()? {
+            let register = reader.read_u32()?;
+            let register = char::from_u32(register)
+                .ok_or_else(|| format!("Invalid register name: {}", register))?;
+
+            registers.insert(register, reader.read_i32()?);
+        }
+
+        let current_instruction = match reader.read_u8()? {
+            0 => None,
+            1 => Some(CpuInstruction::decode(reader)?),
+            other => return Err(format!("Invalid current instruction flag: {}", other)),
+        };
+
+        let ticks_left_for_current_instruction = reader.read_u64()? as usize;
+
+        let encoded_instructions_len = reader.read_len()?;
+        let instructions =
+            CpuInstruction::decode_stream(reader.read_bytes(encoded_instructions_len)?)?.into();
+
+        let interrupt_handlers = (0..reader.read_len()?)
+            .map(|_| {
+                let encoded_handler_len = reader.read_len()?;
+
+                CpuInstruction::decode_stream(reader.read_bytes(encoded_handler_len)?)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let pending_interrupts = (0..reader.read_len()?)
+            .map(|_| reader.read_u64().map(|address| address as usize))
+            .collect::<Result<_, _>>()?;
+
+        Ok(CPU {
+            registers,
+            instructions,
+            current_instruction,
+            ticks_left_for_current_instruction,
+            interrupt_handlers,
+            pending_interrupts,
+        })
+    }
 }
 
 #[derive(Debug, PartialEq)]
 struct CRT {
     display: Vec<Vec<char>>,
+    completed_frames: Vec<Vec<Vec<char>>>,
     current_col: usize,
     current_row: usize,
 }
@@ -169,12 +395,21 @@ struct CRT {
 impl CRT {
     fn new() -> CRT {
         CRT {
-            display: vec![vec!['.'; 40]; 6],
+            display: vec![vec!['.'; DISPLAY_WIDTH]; DISPLAY_HEIGHT],
+            completed_frames: vec![],
             current_col: 0,
             current_row: 0,
         }
     }
 
+    fn frames(&self) -> Vec<Vec<Vec<char>>> {
+        let mut result = self.completed_frames.clone();
+
+        result.push(self.display.clone());
+
+        result
+    }
+
     fn print(&self) {
         println!("{}", ['='; 46].iter().collect::<String>());
 
@@ -191,10 +426,22 @@ impl CRT {
     }
 
     fn run(&mut self, cycle: &Cycle, sprite_center_location: i32) {
-        self.current_row = (cycle.tick.saturating_sub(1) / 40) % 6;
-        self.current_col = cycle.tick.saturating_sub(1) % 40;
+        let pixel = cycle.tick.saturating_sub(1);
+
+        self.current_row = (pixel / DISPLAY_WIDTH) % DISPLAY_HEIGHT;
+        self.current_col = pixel % DISPLAY_WIDTH;
 
         if cycle.state == CycleState::Executing {
+            let display_wrapped =
+                (pixel != 0) && (self.current_row == 0) && (self.current_col == 0);
+
+            if display_wrapped {
+                let blank_display = vec![vec!['.'; DISPLAY_WIDTH]; DISPLAY_HEIGHT];
+
+                self.completed_frames
+                    .push(mem::replace(&mut self.display, blank_display));
+            }
+
             self.draw_sprite(sprite_center_location);
         }
     }
@@ -206,6 +453,61 @@ impl CRT {
             self.display[self.current_row][self.current_col] = '#';
         }
     }
+
+    fn encode(&self, writer: &mut ByteWriter) {
+        writer.write_u64(self.current_col as u64);
+        writer.write_u64(self.current_row as u64);
+
+        writer.write_len(self.completed_frames.len());
+        self.completed_frames
+            .iter()
+            .for_each(|frame| encode_frame(frame, writer));
+
+        encode_frame(&self.display, writer);
+    }
+
+    fn decode(reader: &mut ByteReader) -> Result<CRT, String> {
+        let current_col = reader.read_u64()? as usize;
+        let current_row = reader.read_u64()? as usize;
+
+        let completed_frames = (0..reader.read_len()?)
+            .map(|_| decode_frame(reader))
+            .collect::<Result<_, _>>()?;
+
+        let display = decode_frame(reader)?;
+
+        Ok(CRT {
+            display,
+            completed_frames,
+            current_col,
+            current_row,
+        })
+    }
+}
+
+fn encode_frame(frame: &[Vec<char>], writer: &mut ByteWriter) {
+    writer.write_len(frame.len());
+    writer.write_len(frame.first().map_or(0, |row| row.len()));
+
+    frame
+        .iter()
+        .flatten()
+        .for_each(|&pixel| writer.write_u8((pixel == '#') as u8));
+}
+
+fn decode_frame(reader: &mut ByteReader) -> Result<Vec<Vec<char>>, String> {
+    let height = reader.read_len()?;
+    let width = reader.read_len()?;
+
+    (0..height)
+        .map(|_| {
+            reader.read_bytes(width).map(|row| {
+                row.iter()
+                    .map(|&pixel| if pixel == 1 { '#' } else { '.' })
+                    .collect()
+            })
+        })
+        .collect()
 }
 
 #[derive(Debug, PartialEq)]
@@ -213,6 +515,7 @@ pub struct VideoSystem {
     clock: ClockCircuit,
     cpu: CPU,
     crt: CRT,
+    bus: PeripheralBus,
 }
 
 impl VideoSystem {
@@ -221,7 +524,32 @@ impl VideoSystem {
             clock: ClockCircuit::new(),
             cpu: CPU::new(),
             crt: CRT::new(),
+            bus: PeripheralBus::new(),
+        }
+    }
+
+    pub fn attach_peripheral(&mut self, peripheral: Box<dyn Peripheral>) {
+        self.bus.attach(peripheral);
+    }
+
+    pub fn install_interrupt_handler(&mut self, input: &[String]) -> HandlerAddress {
+        let handler: Vec<CpuInstruction> = input.iter().map(CpuInstruction::from).collect();
+
+        self.cpu.install_interrupt_handler(&handler)
+    }
+
+    pub fn set_interrupt_vector(
+        &mut self,
+        line: InterruptLine,
+        address: HandlerAddress,
+    ) -> Result<(), String> {
+        if self.cpu.interrupt_handlers.len() <= address {
+            return Err(format!("No interrupt handler at address {}", address));
         }
+
+        self.bus.set_interrupt_vector(line, address);
+
+        Ok(())
     }
 
     pub fn print_display(&self) {
@@ -232,6 +560,97 @@ impl VideoSystem {
         self.crt.display.clone()
     }
 
+    pub fn read_display(&self) -> Result<String, String> {
+        crt_font::read_letters(&self.crt.display)
+    }
+
+    pub fn get_frames(&self) -> Vec<Vec<Vec<char>>> {
+        self.crt.frames()
+    }
+
+    /*
+    Peripherals are arbitrary trait objects and can't be written out, so a video system
+    with any attached can't be snapshotted. Interrupt vectors are kept, so peripherals
+    attached again after restoring still reach their handlers.
+    */
+    pub fn snapshot(&self) -> Result<Vec<u8>, String> {
+        if self.bus.has_peripherals() {
+            return Err(String::from(
+                "Can't snapshot a video system with peripherals attached",
+            ));
+        }
+
+        let mut writer = ByteWriter::new();
+
+        writer.write_bytes(SNAPSHOT_MAGIC);
+        self.clock.current_cycle.encode(&mut writer);
+        self.cpu.encode(&mut writer);
+        self.crt.encode(&mut writer);
+
+        writer.write_len(self.bus.interrupt_vectors().len());
+        self.bus
+            .interrupt_vectors()
+            .iter()
+            .for_each(|(&line, &address)| {
+                writer.write_u8(line);
+                writer.write_u64(address as u64);
+            });
+
+        Ok(writer.into_bytes())
+    }
+
+    pub fn restore(bytes: &[u8]) -> Result<VideoSystem, String> {
+        let mut reader = ByteReader::new(bytes);
+
+        if reader.read_bytes(SNAPSHOT_MAGIC.len())? != SNAPSHOT_MAGIC {
+            return Err(String::from("Not a video system snapshot"));
+        }
+
+        let clock = ClockCircuit {
+            current_cycle: Cycle::decode(&mut reader)?,
+        };
+        let cpu = CPU::decode(&mut reader)?;
+        let crt = CRT::decode(&mut reader)?;
+
+        let mut video_system = VideoSystem {
+            clock,
+            cpu,
+            crt,
+            bus: PeripheralBus::new(),
+        };
+
+        for _ in 0..reader.read_len()? {
+            let line = reader.read_u8()?;
+            let address = reader.read_u64()? as usize;
+
+            video_system.set_interrupt_vector(line, address)?;
+        }
+
+        if !reader.is_empty() {
+            return Err(String::from("Trailing bytes after video system snapshot"));
+        }
+
+        Ok(video_system)
+    }
+
+    pub fn current_tick(&self) -> usize {
+        self.clock.current_cycle.tick
+    }
+
+    pub fn number_of_queued_cpu_instructions(&self) -> usize {
+        self.cpu.number_of_queued_instructions()
+    }
+
+    pub fn get_cpu_register_value(&self, register: char) -> Option<i32> {
+        self.cpu.get_register_value(register)
+    }
+
+    pub fn run_until_tick(&mut self, tick: usize) {
+        while !self.cpu.is_finished() && (self.clock.current_cycle.tick <= tick) {
+            self.step();
+        }
+    }
+
     pub fn add_cpu_instructions(&mut self, input: &[String]) {
         let instructions: Vec<CpuInstruction> = input
             .iter()
@@ -241,24 +660,19 @@ impl VideoSystem {
         self.cpu.add_instructions(&instructions);
     }
 
+    pub fn add_decoded_cpu_instructions(&mut self, instructions: &[CpuInstruction]) {
+        self.cpu.add_instructions(instructions);
+    }
+
     pub fn get_cpu_register_signal_strengths_at(
         &mut self,
         register: char,
         ticks: &HashSet<usize>,
     ) -> Vec<Option<i32>> {
         let mut result = Vec::new();
-        let temp_clock = self.clock;
 
-        for cycle in temp_clock {
-            if self.cpu.instructions.is_empty() && self.cpu.current_instruction.is_none() {
-                break;
-            }
-
-            self.cpu.run(&cycle);
-
-            let sprite_center_location = self.cpu.get_register_value('X').unwrap_or(-2);
-
-            self.crt.run(&cycle, sprite_center_location);
+        while !self.cpu.is_finished() {
+            let cycle = self.step();
 
             if ticks.contains(&cycle.tick) && (cycle.state == CycleState::Executing) {
                 result.push(
@@ -269,16 +683,38 @@ impl VideoSystem {
             }
         }
 
-        self.clock = temp_clock;
-
         result
     }
+
+    fn step(&mut self) -> Cycle {
+        let cycle = self
+            .clock
+            .next()
+            .expect("The clock circuit never stops ticking");
+
+        for address in self.bus.broadcast(&cycle) {
+            self.cpu
+                .raise_interrupt(address)
+                .expect("Interrupt vectors only point at installed handlers");
+        }
+
+        self.cpu.run(&cycle);
+
+        let sprite_center_location = self.cpu.get_register_value('X').unwrap_or(-2);
+
+        self.crt.run(&cycle, sprite_center_location);
+
+        cycle
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::device::peripheral::Timer;
+    use crate::util::file_reader::to_string_vector;
+
     #[test]
     fn test_cycle_next_state() {
         let cycle = Cycle::new();
@@ -354,6 +790,8 @@ mod tests {
             instructions: VecDeque::new(),
             current_instruction: None,
             ticks_left_for_current_instruction: 0,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         assert_eq!(cpu, expected);
@@ -368,6 +806,8 @@ mod tests {
             instructions: VecDeque::new(),
             current_instruction: None,
             ticks_left_for_current_instruction: 0,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         cpu.execute(&CpuInstruction::Add(3));
@@ -394,6 +834,8 @@ mod tests {
             instructions: VecDeque::from([CpuInstruction::Add(3), CpuInstruction::Add(-5)]),
             current_instruction: Some(CpuInstruction::NoOp),
             ticks_left_for_current_instruction: 1,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         clock.take(1).for_each(|cycle| {
@@ -421,6 +863,8 @@ mod tests {
             instructions: VecDeque::from([CpuInstruction::Add(-5)]),
             current_instruction: Some(CpuInstruction::Add(3)),
             ticks_left_for_current_instruction: 2,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         clock.take(5).for_each(|cycle| {
@@ -448,6 +892,8 @@ mod tests {
             instructions: VecDeque::from([CpuInstruction::Add(-5)]),
             current_instruction: None,
             ticks_left_for_current_instruction: 0,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         clock.take(9).for_each(|cycle| {
@@ -475,6 +921,8 @@ mod tests {
             instructions: VecDeque::new(),
             current_instruction: Some(CpuInstruction::Add(-5)),
             ticks_left_for_current_instruction: 1,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         clock.take(12).for_each(|cycle| {
@@ -503,6 +951,8 @@ mod tests {
             instructions: VecDeque::new(),
             current_instruction: None,
             ticks_left_for_current_instruction: 0,
+            interrupt_handlers: vec![],
+            pending_interrupts: VecDeque::new(),
         };
 
         for cycle in clock {
@@ -588,6 +1038,7 @@ mod tests {
 
         let expected = CRT {
             display: vec![vec!['.'; 40]; 6],
+            completed_frames: vec![],
             current_col: 2,
             current_row: 0,
         };
@@ -606,6 +1057,7 @@ mod tests {
 
         let expected = CRT {
             display: vec![vec!['.'; 40]; 6],
+            completed_frames: vec![],
             current_col: 39,
             current_row: 5,
         };
@@ -616,4 +1068,229 @@ mod tests {
 
         assert_eq!(crt, expected);
     }
+
+    #[test]
+    fn test_crt_execute_run_cycle_241_starts_new_frame() {
+        let mut crt = CRT::new();
+        let clock = ClockCircuit::new();
+
+        let mut first_frame = vec![vec!['.'; 40]; 6];
+        first_frame[0][1] = '#';
+
+        let mut second_frame = vec![vec!['.'; 40]; 6];
+        second_frame[0][0] = '#';
+
+        clock.take(723).for_each(|cycle| {
+            let sprite_center_location = if cycle.tick == 2 { 1 } else { 100 };
+
+            crt.run(&cycle, sprite_center_location);
+        });
+
+        crt.draw_sprite(0);
+
+        let expected = vec![first_frame, second_frame];
+
+        let result = crt.frames();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_cpu_instruction_stream_round_trip() {
+        let input = vec![
+            CpuInstruction::NoOp,
+            CpuInstruction::Add(3),
+            CpuInstruction::Add(-5),
+        ];
+
+        let encoded = CpuInstruction::encode_stream(&input);
+
+        let expected_encoded = vec![0x00, 0x01, 3, 0, 0, 0, 0x01, 0xfb, 0xff, 0xff, 0xff];
+
+        let result = CpuInstruction::decode_stream(&encoded);
+
+        assert_eq!(encoded, expected_encoded);
+        assert_eq!(result, Ok(input));
+    }
+
+    #[test]
+    fn test_cpu_instruction_decode_stream_unknown_opcode() {
+        let expected = Err(String::from("Unknown opcode: 0x07"));
+
+        let result = CpuInstruction::decode_stream(&[0x00, 0x07]);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_video_system_snapshot_round_trip() {
+        let input = to_string_vector("test_inputs/day_10_part_1.txt").unwrap();
+
+        let mut video_system = VideoSystem::new();
+
+        video_system.add_cpu_instructions(&input);
+        video_system.run_until_tick(100);
+
+        let result = VideoSystem::restore(&video_system.snapshot().unwrap());
+
+        assert_eq!(result, Ok(video_system));
+    }
+
+    #[test]
+    fn test_video_system_resume_from_snapshot() {
+        let input = to_string_vector("test_inputs/day_10_part_1.txt").unwrap();
+        let ticks = HashSet::from([20, 60, 100, 140, 180, 220]);
+
+        let mut uninterrupted = VideoSystem::new();
+        let mut interrupted = VideoSystem::new();
+
+        uninterrupted.add_cpu_instructions(&input);
+        interrupted.add_cpu_instructions(&input);
+
+        let expected =
+            uninterrupted.get_cpu_register_signal_strengths_at('X', &ticks)[3..].to_vec();
+
+        interrupted.run_until_tick(100);
+
+        let mut resumed = VideoSystem::restore(&interrupted.snapshot().unwrap()).unwrap();
+
+        let result = resumed.get_cpu_register_signal_strengths_at('X', &ticks);
+
+        assert_eq!(result, expected);
+        assert_eq!(resumed.get_display(), uninterrupted.get_display());
+    }
+
+    #[test]
+    fn test_video_system_restore_bad_magic() {
+        let expected = Err(String::from("Not a video system snapshot"));
+
+        let result = VideoSystem::restore(b"NOPE");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_cpu_run_handles_interrupt_between_instructions() {
+        let mut cpu = CPU::new();
+        let clock = ClockCircuit::new();
+
+        cpu.add_instructions(&[CpuInstruction::Add(3), CpuInstruction::Add(-5)]);
+
+        let address = cpu.install_interrupt_handler(&[CpuInstruction::Add(10)]);
+
+        let mut result = vec![];
+
+        for cycle in clock.take(18) {
+            if (cycle.tick == 1) && (cycle.state == CycleState::Executing) {
+                cpu.raise_interrupt(address).unwrap();
+            }
+
+            if cycle.state == CycleState::Ending {
+                result.push(cpu.run(&cycle));
+            } else {
+                cpu.run(&cycle);
+            }
+        }
+
+        let expected = vec![1, 4, 4, 14, 14, 9];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_cpu_raise_interrupt_without_handler() {
+        let mut cpu = CPU::new();
+
+        let expected = Err(String::from("No interrupt handler at address 0"));
+
+        let result = cpu.raise_interrupt(0);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_video_system_timer_interrupts() {
+        let input = vec![String::from("noop"); 6];
+        let handler = vec![String::from("addx 2")];
+
+        let mut video_system = VideoSystem::new();
+
+        video_system.add_cpu_instructions(&input);
+
+        let address = video_system.install_interrupt_handler(&handler);
+
+        video_system.set_interrupt_vector(3, address).unwrap();
+        video_system.attach_peripheral(Box::new(Timer::new(3, 4)));
+
+        let ticks = HashSet::from([1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let expected = vec![
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(4),
+            Some(5),
+            Some(6),
+            Some(21),
+            Some(24),
+        ];
+
+        let result = video_system.get_cpu_register_signal_strengths_at('X', &ticks);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_video_system_set_interrupt_vector_without_handler() {
+        let mut video_system = VideoSystem::new();
+
+        let expected = Err(String::from("No interrupt handler at address 2"));
+
+        let result = video_system.set_interrupt_vector(1, 2);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_video_system_snapshot_with_peripherals() {
+        let mut video_system = VideoSystem::new();
+
+        video_system.attach_peripheral(Box::new(Timer::new(3, 4)));
+
+        let expected = Err(String::from(
+            "Can't snapshot a video system with peripherals attached",
+        ));
+
+        let result = video_system.snapshot();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_video_system_interrupts_after_restore() {
+        let input = vec![String::from("noop"); 6];
+        let handler = vec![String::from("addx 2")];
+
+        let mut video_system = VideoSystem::new();
+
+        video_system.add_cpu_instructions(&input);
+
+        let address = video_system.install_interrupt_handler(&handler);
+
+        video_system.set_interrupt_vector(3, address).unwrap();
+
+        let mut restored = VideoSystem::restore(&video_system.snapshot().unwrap()).unwrap();
+
+        assert_eq!(restored, video_system);
+
+        restored.attach_peripheral(Box::new(Timer::new(3, 4)));
+
+        let ticks = HashSet::from([6, 7, 8]);
+
+        let expected = vec![Some(6), Some(21), Some(24)];
+
+        let result = restored.get_cpu_register_signal_strengths_at('X', &ticks);
+
+        assert_eq!(result, expected);
+    }
 }
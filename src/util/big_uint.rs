@@ -0,0 +1,319 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+use std::str::FromStr;
+
+/*
+Arbitrary precision unsigned integers, stored as base 2^32 limbs with the least
+significant limb first. Zero has no limbs, and the last limb is never zero.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> BigUint {
+        BigUint { limbs: vec![] }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn normalized(mut self) -> BigUint {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+
+        self
+    }
+
+    pub fn checked_sub(&self, other: &BigUint) -> Option<BigUint> {
+        if self < other {
+            return None;
+        }
+
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+
+        for (index, &limb) in self.limbs.iter().enumerate() {
+            let subtrahend = other.limbs.get(index).cloned().unwrap_or(0) as i64 + borrow;
+            let mut difference = limb as i64 - subtrahend;
+
+            if difference < 0 {
+                difference += 1 << 32;
+                borrow = 1;
+            } else {
+                borrow = 0;
+            }
+
+            limbs.push(difference as u32);
+        }
+
+        Some(BigUint { limbs }.normalized())
+    }
+
+    pub fn div_rem_u64(&self, divisor: u64) -> Option<(BigUint, u64)> {
+        if divisor == 0 {
+            return None;
+        }
+
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder: u128 = 0;
+
+        for (index, &limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | limb as u128;
+
+            quotient[index] = (current / divisor as u128) as u32;
+            remainder = current % divisor as u128;
+        }
+
+        Some((BigUint { limbs: quotient }.normalized(), remainder as u64))
+    }
+
+    // Long division one bit at a time, which is plenty for the sizes we divide.
+    pub fn div_rem(&self, divisor: &BigUint) -> Option<(BigUint, BigUint)> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        if divisor.limbs.len() <= 2 {
+            let (quotient, remainder) = self.div_rem_u64(divisor.to_u64().unwrap())?;
+
+            return Some((quotient, BigUint::from(remainder)));
+        }
+
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut remainder = BigUint::zero();
+
+        for bit in (0..self.limbs.len() * 32).rev() {
+            remainder = remainder.shifted_left_with(self.bit(bit));
+
+            if &remainder >= divisor {
+                remainder = remainder.checked_sub(divisor).unwrap();
+                quotient[bit / 32] |= 1 << (bit % 32);
+            }
+        }
+
+        Some((BigUint { limbs: quotient }.normalized(), remainder))
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs.as_slice() {
+            [] => Some(0),
+            [low] => Some(*low as u64),
+            [low, high] => Some(((*high as u64) << 32) | *low as u64),
+            _ => None,
+        }
+    }
+
+    fn bit(&self, index: usize) -> bool {
+        (self.limbs[index / 32] >> (index % 32)) & 1 == 1
+    }
+
+    fn shifted_left_with(mut self, low_bit: bool) -> BigUint {
+        let mut carry = low_bit as u32;
+
+        for limb in self.limbs.iter_mut() {
+            let next_carry = *limb >> 31;
+
+            *limb = (*limb << 1) | carry;
+            carry = next_carry;
+        }
+
+        if carry != 0 {
+            self.limbs.push(carry);
+        }
+
+        self
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> BigUint {
+        BigUint {
+            limbs: vec![value as u32, (value >> 32) as u32],
+        }
+        .normalized()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, other: &BigUint) -> BigUint {
+        let length = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(length + 1);
+        let mut carry = 0;
+
+        for index in 0..length {
+            let sum = self.limbs.get(index).cloned().unwrap_or(0) as u64
+                + other.limbs.get(index).cloned().unwrap_or(0) as u64
+                + carry;
+
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+
+        limbs.push(carry as u32);
+
+        BigUint { limbs }.normalized()
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+
+        for (i, &left) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+
+            for (j, &right) in other.limbs.iter().enumerate() {
+                let product = left as u64 * right as u64 + limbs[i + j] as u64 + carry;
+
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+
+        BigUint { limbs }.normalized()
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        let mut chunks = vec![];
+        let mut current = self.clone();
+
+        while !current.is_zero() {
+            let (quotient, remainder) = current.div_rem_u64(1_000_000_000).unwrap();
+
+            chunks.push(remainder);
+            current = quotient;
+        }
+
+        write!(f, "{}", chunks.pop().unwrap())?;
+
+        chunks
+            .iter()
+            .rev()
+            .try_for_each(|chunk| write!(f, "{:09}", chunk))
+    }
+}
+
+impl FromStr for BigUint {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<BigUint, String> {
+        if input.is_empty() {
+            return Err(String::from("Can't parse an empty string as a number"));
+        }
+
+        let ten = BigUint::from(10);
+
+        input.chars().try_fold(BigUint::zero(), |acc, c| {
+            c.to_digit(10)
+                .map(|digit| &(&acc * &ten) + &BigUint::from(digit as u64))
+                .ok_or_else(|| format!("Invalid digit '{}' in {}", c, input))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(input: &str) -> BigUint {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_add_carries() {
+        let expected = big("18446744073709551616");
+
+        let result = &BigUint::from(u64::MAX) + &BigUint::from(1);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_mul() {
+        let two_to_the_64 = big("18446744073709551616");
+
+        let expected = big("340282366920938463463374607431768211456");
+
+        let result = &two_to_the_64 * &two_to_the_64;
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_checked_sub() {
+        let expected = Some(big("18446744073709551615"));
+
+        let result = big("18446744073709551616").checked_sub(&BigUint::from(1));
+
+        assert_eq!(result, expected);
+        assert_eq!(BigUint::from(1).checked_sub(&BigUint::from(2)), None);
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let dividend = big("340282366920938463463374607431768211459");
+        let divisor = big("18446744073709551616000");
+
+        let expected = Some((big("18446744073709551"), big("11363194349405083795459")));
+
+        let result = dividend.div_rem(&divisor);
+
+        assert_eq!(result, expected);
+        assert_eq!(dividend.div_rem(&BigUint::zero()), None);
+    }
+
+    #[test]
+    fn test_div_rem_u64() {
+        let expected = Some((big("113427455640312821154458202477256070485"), 1));
+
+        let result = big("340282366920938463463374607431768211456").div_rem_u64(3);
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let input = [
+            "0",
+            "7",
+            "1000000000",
+            "340282366920938463463374607431768211456",
+        ];
+
+        let result: Vec<String> = input.iter().map(|line| big(line).to_string()).collect();
+
+        assert_eq!(result, input);
+    }
+}
@@ -0,0 +1,364 @@
+use std::iter::successors;
+
+use super::point_2d::Point2d;
+
+// Up, right, down and left, in the order the neighbours are visited.
+const ORTHOGONAL_STEPS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+const ALL_STEPS: [(i32, i32); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/*
+A rectangular grid of cells stored row by row. Points are `Point2d<i32>` with `x` as
+the column and `y` as the row, so neighbour arithmetic can step outside the grid and
+`get` simply returns `None` there.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, String> {
+        let width = rows.first().map_or(0, |row| row.len());
+        let height = rows.len();
+
+        if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(format!(
+                "Row {} has {} cells, expected {}",
+                index,
+                row.len(),
+                width
+            ));
+        }
+
+        Ok(Grid {
+            cells: rows.into_iter().flatten().collect(),
+            width,
+            height,
+        })
+    }
+
+    pub fn parse<F>(input: &[String], parse_cell: F) -> Result<Grid<T>, String>
+    where
+        F: Fn(char) -> Option<T>,
+    {
+        let rows = input
+            .iter()
+            .enumerate()
+            .map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(x, c)| {
+                        parse_cell(c).ok_or_else(|| format!("Unexpected '{}' at ({}, {})", c, x, y))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, point: &Point2d<i32>) -> bool {
+        self.index_of(point).is_some()
+    }
+
+    pub fn get(&self, point: &Point2d<i32>) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: &Point2d<i32>) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.cells[index])
+    }
+
+    // Returns the previous value, or `None` if the point is outside the grid.
+    pub fn set(&mut self, point: &Point2d<i32>, value: T) -> Option<T> {
+        self.get_mut(point)
+            .map(|cell| std::mem::replace(cell, value))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = Point2d<i32>> + '_ {
+        (0..self.len()).map(|index| self.point_at(index))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point2d<i32>, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| (self.point_at(index), cell))
+    }
+
+    pub fn position<P>(&self, predicate: P) -> Option<Point2d<i32>>
+    where
+        P: Fn(&T) -> bool,
+    {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|index| self.point_at(index))
+    }
+
+    pub fn neighbours_4(&self, point: &Point2d<i32>) -> impl Iterator<Item = Point2d<i32>> + '_ {
+        self.neighbours_with(*point, &ORTHOGONAL_STEPS)
+    }
+
+    // Clockwise from straight up.
+    pub fn neighbours_8(&self, point: &Point2d<i32>) -> impl Iterator<Item = Point2d<i32>> + '_ {
+        self.neighbours_with(*point, &ALL_STEPS)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = &T> + '_ {
+        self.cells.iter().skip(y * self.width).take(self.width)
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        let height = if x < self.width { self.height } else { 0 };
+
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(self.width.max(1))
+            .take(height)
+    }
+
+    // The cells from `start` (exclusive) in steps of `step` until leaving the grid.
+    pub fn ray(
+        &self,
+        start: &Point2d<i32>,
+        step: (i32, i32),
+    ) -> impl Iterator<Item = (Point2d<i32>, &T)> + '_ {
+        successors(Some(start.add_t(step)), move |point| {
+            Some(point.add_t(step))
+        })
+        .map_while(move |point| self.get(&point).map(|cell| (point, cell)))
+    }
+
+    pub fn map<U, F>(&self, convert: F) -> Grid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.iter().map(convert).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn render<F>(&self, to_char: F) -> String
+    where
+        F: Fn(&T) -> char,
+    {
+        self.rows()
+            .take(self.height)
+            .map(|row| row.iter().map(&to_char).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn index_of(&self, point: &Point2d<i32>) -> Option<usize> {
+        let x = usize::try_from(point.x).ok().filter(|x| *x < self.width)?;
+        let y = usize::try_from(point.y).ok().filter(|y| *y < self.height)?;
+
+        Some(y * self.width + x)
+    }
+
+    fn point_at(&self, index: usize) -> Point2d<i32> {
+        Point2d::new((index % self.width) as i32, (index / self.width) as i32)
+    }
+
+    fn neighbours_with<'a>(
+        &'a self,
+        point: Point2d<i32>,
+        steps: &'a [(i32, i32)],
+    ) -> impl Iterator<Item = Point2d<i32>> + 'a {
+        steps
+            .iter()
+            .map(move |step| point.add_t(*step))
+            .filter(|neighbour| self.contains(neighbour))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_grid() -> Grid<u32> {
+        let input = vec![
+            String::from("30373"),
+            String::from("25512"),
+            String::from("65332"),
+        ];
+
+        Grid::parse(&input, |c| c.to_digit(10)).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let expected = Grid::from_rows(vec![
+            vec![3, 0, 3, 7, 3],
+            vec![2, 5, 5, 1, 2],
+            vec![6, 5, 3, 3, 2],
+        ])
+        .unwrap();
+
+        let result = get_grid();
+
+        assert_eq!(result, expected);
+        assert_eq!((result.width(), result.height(), result.len()), (5, 3, 15));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = [
+            vec![String::from("123"), String::from("1x3")],
+            vec![String::from("123"), String::from("12")],
+        ];
+
+        let expected = vec![
+            Err(String::from("Unexpected 'x' at (1, 1)")),
+            Err(String::from("Row 1 has 2 cells, expected 3")),
+        ];
+
+        let result: Vec<Result<Grid<u32>, String>> = input
+            .iter()
+            .map(|lines| Grid::parse(lines, |c| c.to_digit(10)))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_get_is_bounds_checked() {
+        let mut grid = get_grid();
+
+        let expected = vec![Some(&3), Some(&7), None, None, None];
+
+        let result: Vec<Option<&u32>> = [(0, 0), (3, 0), (-1, 0), (5, 0), (0, 3)]
+            .iter()
+            .map(|&point| grid.get(&Point2d::from(point)))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(grid.set(&Point2d::new(4, 2), 9), Some(2));
+        assert_eq!(grid.set(&Point2d::new(4, 3), 9), None);
+        assert_eq!(grid.get(&Point2d::new(4, 2)), Some(&9));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = get_grid();
+
+        let expected_4 = vec![Point2d::new(1, 0), Point2d::new(0, 1)];
+        let expected_8 = vec![
+            Point2d::new(1, 0),
+            Point2d::new(2, 0),
+            Point2d::new(2, 1),
+            Point2d::new(2, 2),
+            Point2d::new(1, 2),
+            Point2d::new(0, 2),
+            Point2d::new(0, 1),
+            Point2d::new(0, 0),
+        ];
+
+        let result_4: Vec<Point2d<i32>> = grid.neighbours_4(&Point2d::new(0, 0)).collect();
+        let result_8: Vec<Point2d<i32>> = grid.neighbours_8(&Point2d::new(1, 1)).collect();
+
+        assert_eq!(result_4, expected_4);
+        assert_eq!(result_8, expected_8);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = get_grid();
+
+        let expected_row = vec![2, 5, 5, 1, 2];
+        let expected_column = vec![7, 1, 3];
+
+        let result_row: Vec<u32> = grid.row(1).cloned().collect();
+        let result_column: Vec<u32> = grid.column(3).cloned().collect();
+
+        assert_eq!(result_row, expected_row);
+        assert_eq!(result_column, expected_column);
+        assert_eq!(grid.row(3).count(), 0);
+        assert_eq!(grid.column(5).count(), 0);
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = get_grid();
+
+        let expected = vec![(Point2d::new(3, 1), &1), (Point2d::new(4, 0), &3)];
+
+        let result: Vec<(Point2d<i32>, &u32)> = grid.ray(&Point2d::new(2, 2), (1, -1)).collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(grid.ray(&Point2d::new(0, 0), (-1, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_position_and_iter() {
+        let grid = get_grid();
+
+        let expected = Some(Point2d::new(3, 0));
+
+        let result = grid.position(|height| *height == 7);
+
+        assert_eq!(result, expected);
+        assert_eq!(grid.iter().nth(6), Some((Point2d::new(1, 1), &5)));
+        assert_eq!(grid.points().last(), Some(Point2d::new(4, 2)));
+    }
+
+    #[test]
+    fn test_map_and_render() {
+        let grid = get_grid();
+
+        let expected = ["#.###", ".##..", "####."].join("\n");
+
+        let result = grid
+            .map(|height| *height >= 3)
+            .render(|tall| if *tall { '#' } else { '.' });
+
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,242 @@
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const MAX_STORED_BLOCK_SIZE: usize = 65_535;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Rgb {
+    pub fn new(red: u8, green: u8, blue: u8) -> Rgb {
+        Rgb { red, green, blue }
+    }
+
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+pub fn export_colors(pixels: &[Vec<Rgb>], format: ImageFormat, scale: usize) -> Vec<u8> {
+    match format {
+        ImageFormat::Ppm => colors_to_ppm(pixels, scale),
+        ImageFormat::Png => colors_to_png(pixels, scale),
+        ImageFormat::Svg => colors_to_svg(pixels, scale).into_bytes(),
+    }
+}
+
+pub fn colors_to_ppm(pixels: &[Vec<Rgb>], scale: usize) -> Vec<u8> {
+    let (width, height) = image_size(pixels, scale);
+
+    let mut result = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+    for row in scaled_rows(pixels, scale) {
+        result.extend(row);
+    }
+
+    result
+}
+
+pub fn colors_to_png(pixels: &[Vec<Rgb>], scale: usize) -> Vec<u8> {
+    let (width, height) = image_size(pixels, scale);
+
+    let mut header = vec![];
+
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, truecolor, deflate, adaptive filtering, no interlace
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut raw_data = vec![];
+
+    for row in scaled_rows(pixels, scale) {
+        // Filter type "None" for every scanline
+        raw_data.push(0);
+        raw_data.extend(row);
+    }
+
+    let mut result = PNG_SIGNATURE.to_vec();
+
+    push_png_chunk(&mut result, b"IHDR", &header);
+    push_png_chunk(&mut result, b"IDAT", &zlib_stored(&raw_data));
+    push_png_chunk(&mut result, b"IEND", &[]);
+
+    result
+}
+
+pub fn colors_to_svg(pixels: &[Vec<Rgb>], scale: usize) -> String {
+    let (width, height) = image_size(pixels, scale);
+
+    let mut result = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+
+    for (row_index, row) in pixels.iter().enumerate() {
+        for (col_index, color) in row.iter().enumerate() {
+            result.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                col_index * scale,
+                row_index * scale,
+                scale,
+                scale,
+                color.hex()
+            ));
+        }
+    }
+
+    result.push_str("</svg>\n");
+
+    result
+}
+
+pub fn image_size<T>(pixels: &[Vec<T>], scale: usize) -> (usize, usize) {
+    let width = pixels.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    (width * scale, pixels.len() * scale)
+}
+
+fn scaled_rows(pixels: &[Vec<Rgb>], scale: usize) -> Vec<Vec<u8>> {
+    let mut result = vec![];
+
+    for row in pixels.iter() {
+        let mut scaled_row = Vec::with_capacity(row.len() * scale * 3);
+
+        for color in row {
+            for _ in 0..scale {
+                scaled_row.extend([color.red, color.green, color.blue]);
+            }
+        }
+
+        for _ in 0..scale {
+            result.push(scaled_row.clone());
+        }
+    }
+
+    result
+}
+
+fn push_png_chunk(buffer: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let mut type_and_data = chunk_type.to_vec();
+
+    type_and_data.extend(data);
+
+    buffer.extend((data.len() as u32).to_be_bytes());
+    buffer.extend(&type_and_data);
+    buffer.extend(crc32(&type_and_data).to_be_bytes());
+}
+
+/*
+PNG requires zlib data, but nothing says it has to be compressed. Stored
+(uncompressed) deflate blocks keep the encoder tiny, and the images are small.
+*/
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+
+    if blocks.peek().is_none() {
+        result.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let is_final_block = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        result.push(is_final_block as u8);
+        result.extend(length.to_le_bytes());
+        result.extend((!length).to_le_bytes());
+        result.extend(block);
+    }
+
+    result.extend(adler32(data).to_be_bytes());
+
+    result
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffff_u32;
+
+    for &byte in data {
+        crc ^= byte as u32;
+
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1_u32;
+    let mut b = 0_u32;
+
+    for &byte in data {
+        a = (a + byte as u32) % 65_521;
+        b = (b + a) % 65_521;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        let expected = 0xcbf4_3926;
+
+        let result = crc32(b"123456789");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_adler32() {
+        let expected = 0x11e6_0398;
+
+        let result = adler32(b"Wikipedia");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        let expected = vec![
+            0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27,
+        ];
+
+        let result = zlib_stored(b"abc");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_colors_to_svg() {
+        let pixels = vec![vec![Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)]];
+
+        let expected = [
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"4\" height=\"2\" viewBox=\"0 0 4 2\">",
+            "  <rect x=\"0\" y=\"0\" width=\"2\" height=\"2\" fill=\"#ff0000\"/>",
+            "  <rect x=\"2\" y=\"0\" width=\"2\" height=\"2\" fill=\"#0000ff\"/>",
+            "</svg>\n",
+        ]
+        .join("\n");
+
+        let result = colors_to_svg(&pixels, 2);
+
+        assert_eq!(result, expected);
+    }
+}
@@ -1,4 +1,9 @@
+pub mod big_uint;
 pub mod file_reader;
+pub mod grid;
+pub mod image;
 pub mod location;
 pub mod math;
 pub mod point_2d;
+pub mod random;
+pub mod search;
@@ -0,0 +1,99 @@
+/*
+SplitMix64, which is plenty for generating test data and keeps us dependency free.
+The same seed always produces the same sequence.
+*/
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut result = self.state;
+
+        result = (result ^ (result >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        result = (result ^ (result >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        result ^ (result >> 31)
+    }
+
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            0
+        } else {
+            (self.next_u64() % (bound as u64)) as usize
+        }
+    }
+
+    pub fn next_in_range(&mut self, min: usize, max: usize) -> usize {
+        min + self.next_below(max.saturating_sub(min) + 1)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() & 1 == 1
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.next_below(items.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_u64() {
+        let mut random = Random::new(1_234_567);
+
+        let expected = vec![6_457_827_717_110_365_317, 3_203_168_211_198_807_973];
+
+        let result = vec![random.next_u64(), random.next_u64()];
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+
+        let expected: Vec<u64> = (0..10).map(|_| first.next_u64()).collect();
+
+        let result: Vec<u64> = (0..10).map(|_| second.next_u64()).collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_next_in_range() {
+        let mut random = Random::new(7);
+
+        let result: Vec<usize> = (0..1_000).map(|_| random.next_in_range(3, 5)).collect();
+
+        assert!(result.iter().all(|value| (3..=5).contains(value)));
+        assert!(result.contains(&3));
+        assert!(result.contains(&5));
+    }
+
+    #[test]
+    fn test_choose_empty() {
+        let mut random = Random::new(7);
+
+        let items: [char; 0] = [];
+
+        let result = random.choose(&items);
+
+        assert_eq!(result, None);
+    }
+}
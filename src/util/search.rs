@@ -0,0 +1,378 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/*
+What a search found: the cheapest known cost to every node it reached and the node each
+one was reached from, so any path can be rebuilt by walking the predecessors back to a
+start. `goal` is the node that stopped the search, if any did.
+*/
+#[derive(Debug, Clone)]
+pub struct SearchTree<N, C> {
+    costs: HashMap<N, C>,
+    predecessors: HashMap<N, N>,
+    goal: Option<N>,
+}
+
+impl<N, C> SearchTree<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy,
+{
+    fn new() -> SearchTree<N, C> {
+        SearchTree {
+            costs: HashMap::new(),
+            predecessors: HashMap::new(),
+            goal: None,
+        }
+    }
+
+    pub fn goal(&self) -> Option<&N> {
+        self.goal.as_ref()
+    }
+
+    pub fn costs(&self) -> &HashMap<N, C> {
+        &self.costs
+    }
+
+    pub fn cost_to(&self, node: &N) -> Option<C> {
+        self.costs.get(node).cloned()
+    }
+
+    // The node `node` was reached from, or `None` for a start or an unreached node.
+    pub fn predecessor(&self, node: &N) -> Option<&N> {
+        self.predecessors.get(node)
+    }
+
+    // From the start the node was reached from up to and including the node itself.
+    pub fn path_to(&self, node: &N) -> Option<Vec<N>> {
+        if !self.costs.contains_key(node) {
+            return None;
+        }
+
+        let mut result = vec![node.clone()];
+
+        while let Some(previous) = self.predecessors.get(result.last().unwrap()) {
+            result.push(previous.clone());
+        }
+
+        result.reverse();
+
+        Some(result)
+    }
+
+    pub fn path_to_goal(&self) -> Option<Vec<N>> {
+        self.goal.as_ref().and_then(|goal| self.path_to(goal))
+    }
+}
+
+/*
+Every step costs one. Nodes are expanded in the order they were found, so the first
+start and the first neighbour listed win ties between equally short paths.
+*/
+pub fn breadth_first<N, S, F, I, G>(
+    starts: S,
+    mut neighbours: F,
+    mut is_goal: G,
+) -> SearchTree<N, usize>
+where
+    N: Eq + Hash + Clone,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    G: FnMut(&N) -> bool,
+{
+    let mut result = SearchTree::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if !result.costs.contains_key(&start) {
+            result.costs.insert(start.clone(), 0);
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            result.goal = Some(node);
+
+            break;
+        }
+
+        let cost = result.costs[&node];
+
+        for next in neighbours(&node) {
+            if !result.costs.contains_key(&next) {
+                result.costs.insert(next.clone(), cost + 1);
+                result.predecessors.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    result
+}
+
+pub fn dijkstra<N, C, S, F, I, G>(starts: S, neighbours: F, is_goal: G) -> SearchTree<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    G: FnMut(&N) -> bool,
+{
+    a_star(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/*
+`heuristic` must never overestimate the remaining cost to a goal, otherwise the path
+found may not be the cheapest. Costs of nodes that were still queued when a goal
+stopped the search are only upper bounds.
+*/
+pub fn a_star<N, C, S, F, I, H, G>(
+    starts: S,
+    mut neighbours: F,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> SearchTree<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Add<Output = C> + Default,
+    S: IntoIterator<Item = N>,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    H: FnMut(&N) -> C,
+    G: FnMut(&N) -> bool,
+{
+    let mut result = SearchTree::new();
+    let mut queue = BinaryHeap::new();
+    let mut settled = HashSet::new();
+    let mut order = 0;
+
+    for start in starts {
+        if !result.costs.contains_key(&start) {
+            result.costs.insert(start.clone(), C::default());
+            queue.push(QueueEntry {
+                priority: heuristic(&start),
+                order,
+                cost: C::default(),
+                node: start,
+            });
+            order += 1;
+        }
+    }
+
+    while let Some(QueueEntry { node, cost, .. }) = queue.pop() {
+        if cost > result.costs[&node] || !settled.insert(node.clone()) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            result.goal = Some(node);
+
+            break;
+        }
+
+        for (next, step_cost) in neighbours(&node) {
+            let next_cost = cost + step_cost;
+
+            if result
+                .costs
+                .get(&next)
+                .is_none_or(|known_cost| next_cost < *known_cost)
+            {
+                result.costs.insert(next.clone(), next_cost);
+                result.predecessors.insert(next.clone(), node.clone());
+                queue.push(QueueEntry {
+                    priority: next_cost + heuristic(&next),
+                    order,
+                    cost: next_cost,
+                    node: next,
+                });
+                order += 1;
+            }
+        }
+    }
+
+    result
+}
+
+// Ordered so the max-heap pops the lowest priority first, and the oldest entry on ties.
+struct QueueEntry<N, C> {
+    priority: C,
+    order: usize,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> Ord for QueueEntry<N, C> {
+    fn cmp(&self, other: &QueueEntry<N, C>) -> Ordering {
+        other
+            .priority
+            .cmp(&self.priority)
+            .then_with(|| other.order.cmp(&self.order))
+    }
+}
+
+impl<N, C: Ord> PartialOrd for QueueEntry<N, C> {
+    fn partial_cmp(&self, other: &QueueEntry<N, C>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> PartialEq for QueueEntry<N, C> {
+    fn eq(&self, other: &QueueEntry<N, C>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C: Ord> Eq for QueueEntry<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util::grid::Grid;
+    use crate::util::point_2d::Point2d;
+
+    // '#' is a wall and digits cost that much to step onto.
+    fn get_grid() -> Grid<char> {
+        let input = vec![
+            String::from("11111"),
+            String::from("1###1"),
+            String::from("19191"),
+            String::from("1#1#1"),
+            String::from("11111"),
+        ];
+
+        Grid::parse(&input, Some).unwrap()
+    }
+
+    fn open_neighbours(grid: &Grid<char>, point: &Point2d<i32>) -> Vec<Point2d<i32>> {
+        grid.neighbours_4(point)
+            .filter(|next| grid.get(next) != Some(&'#'))
+            .collect()
+    }
+
+    fn weighted_neighbours(grid: &Grid<char>, point: &Point2d<i32>) -> Vec<(Point2d<i32>, u32)> {
+        open_neighbours(grid, point)
+            .into_iter()
+            .map(|next| (next, grid.get(&next).unwrap().to_digit(10).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_breadth_first_path() {
+        let grid = get_grid();
+        let goal = Point2d::new(2, 3);
+
+        let search = breadth_first(
+            [Point2d::new(0, 0)],
+            |point| open_neighbours(&grid, point),
+            |point| *point == goal,
+        );
+
+        let expected = Some(vec![
+            Point2d::new(0, 0),
+            Point2d::new(0, 1),
+            Point2d::new(0, 2),
+            Point2d::new(1, 2),
+            Point2d::new(2, 2),
+            Point2d::new(2, 3),
+        ]);
+
+        let result = search.path_to_goal();
+
+        assert_eq!(result, expected);
+        assert_eq!(search.cost_to(&goal), Some(5));
+    }
+
+    #[test]
+    fn test_breadth_first_multi_source() {
+        let grid = get_grid();
+
+        let search = breadth_first(
+            [Point2d::new(0, 0), Point2d::new(4, 4)],
+            |point| open_neighbours(&grid, point),
+            |_| false,
+        );
+
+        let expected = vec![Some(2), Some(4), Some(0), None];
+
+        let result: Vec<Option<usize>> = [(2, 0), (4, 0), (4, 4), (1, 1)]
+            .iter()
+            .map(|&point| search.cost_to(&Point2d::from(point)))
+            .collect();
+
+        assert_eq!(result, expected);
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.costs().len(), 20);
+    }
+
+    #[test]
+    fn test_dijkstra_avoids_expensive_cells() {
+        let grid = get_grid();
+        let goal = Point2d::new(2, 3);
+
+        let search = dijkstra(
+            [Point2d::new(0, 0)],
+            |point| weighted_neighbours(&grid, point),
+            |point| *point == goal,
+        );
+
+        let expected = Some(7);
+
+        let result = search.cost_to(&goal);
+
+        assert_eq!(result, expected);
+        assert_eq!(search.path_to_goal().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_a_star_agrees_with_dijkstra() {
+        let grid = get_grid();
+
+        let expected: Vec<Option<u32>> = grid
+            .points()
+            .map(|goal| {
+                dijkstra(
+                    [Point2d::new(0, 0)],
+                    |point| weighted_neighbours(&grid, point),
+                    |point| *point == goal,
+                )
+                .cost_to(&goal)
+            })
+            .collect();
+
+        let result: Vec<Option<u32>> = grid
+            .points()
+            .map(|goal| {
+                a_star(
+                    [Point2d::new(0, 0)],
+                    |point| weighted_neighbours(&grid, point),
+                    |point| ((goal.x - point.x).abs() + (goal.y - point.y).abs()) as u32,
+                    |point| *point == goal,
+                )
+                .cost_to(&goal)
+            })
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let grid = get_grid();
+
+        let search = breadth_first(
+            [Point2d::new(0, 0)],
+            |point| open_neighbours(&grid, point),
+            |point| *point == Point2d::new(2, 1),
+        );
+
+        assert_eq!(search.path_to_goal(), None);
+        assert_eq!(search.path_to(&Point2d::new(2, 1)), None);
+    }
+}